@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use super::ray::Ray;
+
+/// Axis-aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    /// Lower left corner
+    pub min: Vec2,
+    /// Upper right corner
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Box with the given center and half of its width and height
+    pub fn from_center(center: Vec2, half_extents: Vec2) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// Smallest box enclosing all points
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        points.into_iter().fold(
+            Self::new(Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |aabb, point| Self::new(aabb.min.min(point), aabb.max.max(point)),
+        )
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn half_extents(&self) -> Vec2 {
+        (self.max - self.min) / 2.0
+    }
+
+    /// Whether the boxes overlap or touch
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// Whether the point lies inside or on the border of the box
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.min.cmple(point).all() && point.cmple(self.max).all()
+    }
+
+    /// Whether the ray passes through the box up to the maximal time of impact
+    pub fn intersects_ray(&self, ray: &Ray, max_toi: f32) -> bool {
+        // Times of impact at which the ray crosses the lower and upper bounds along each axis
+        let inverse_direction = ray.direction.recip();
+        let toi1 = (self.min - ray.origin) * inverse_direction;
+        let toi2 = (self.max - ray.origin) * inverse_direction;
+        let enter = toi1.min(toi2).max_element().max(0.0);
+        let exit = toi1.max(toi2).min_element().min(max_toi);
+        enter <= exit
+    }
+
+    /// Box grown by the margin on every side
+    pub fn expanded(&self, margin: f32) -> Self {
+        Self::new(
+            self.min - Vec2::splat(margin),
+            self.max + Vec2::splat(margin),
+        )
+    }
+
+    /// Smallest box enclosing both boxes
+    pub fn merged(&self, other: &Aabb) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points() {
+        let aabb = Aabb::from_points([
+            Vec2::new(1.0, -2.0),
+            Vec2::new(-3.0, 0.5),
+            Vec2::new(0.0, 4.0),
+        ]);
+        assert_eq!(aabb, Aabb::new(Vec2::new(-3.0, -2.0), Vec2::new(1.0, 4.0)));
+        assert_eq!(aabb.center(), Vec2::new(-1.0, 1.0));
+        assert_eq!(aabb.half_extents(), Vec2::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn intersects() {
+        let aabb = Aabb::new(Vec2::ZERO, Vec2::ONE);
+        assert!(aabb.intersects(&Aabb::new(Vec2::splat(0.5), Vec2::splat(2.0))));
+        assert!(aabb.intersects(&Aabb::new(Vec2::ONE, Vec2::splat(2.0))));
+        assert!(!aabb.intersects(&Aabb::new(Vec2::new(1.5, 0.0), Vec2::new(2.0, 1.0))));
+        assert!(!aabb.intersects(&Aabb::new(Vec2::new(0.0, -2.0), Vec2::new(1.0, -0.5))));
+    }
+
+    #[test]
+    fn intersects_ray() {
+        let aabb = Aabb::new(Vec2::ZERO, Vec2::ONE);
+        let ray = Ray::new(Vec2::new(-1.0, 0.5), Vec2::X);
+        assert!(aabb.intersects_ray(&ray, f32::MAX));
+        assert!(!aabb.intersects_ray(&ray, 0.5));
+        assert!(!aabb.intersects_ray(&Ray::new(Vec2::new(-1.0, 0.5), -Vec2::X), f32::MAX));
+        assert!(aabb.intersects_ray(&Ray::new(Vec2::splat(0.5), Vec2::X), 0.0));
+        assert!(!aabb.intersects_ray(&Ray::new(Vec2::new(-1.5, 0.0), Vec2::ONE), f32::MAX));
+        assert!(aabb.intersects_ray(&Ray::new(Vec2::new(-0.5, 0.0), Vec2::ONE), f32::MAX));
+    }
+}
@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+
+use super::{aabb::Aabb, collider::Collider, filter::CollisionGroups, ray::Ray};
+
+/// Finds the pairs of colliders whose bounds overlap with the sweep and prune algorithm.
+///
+/// Only these pairs have to be checked by the exact collision tests.
+#[derive(Resource, Default)]
+pub struct BroadPhase {
+    /// Colliders with their bounds, sorted by the lower bound along the x-axis
+    entries: Vec<BroadPhaseEntry>,
+    /// Pairs of colliders whose bounds overlap
+    pairs: Vec<(Entity, Entity)>,
+}
+
+struct BroadPhaseEntry {
+    entity: Entity,
+    aabb: Aabb,
+    groups: CollisionGroups,
+}
+
+impl BroadPhase {
+    /// Pairs of colliders whose bounds overlap
+    pub fn pairs(&self) -> &[(Entity, Entity)] {
+        &self.pairs
+    }
+
+    /// Colliders whose bounds overlap or touch the box, with their collision groups
+    pub fn query_aabb(&self, aabb: Aabb) -> impl Iterator<Item = (Entity, CollisionGroups)> + '_ {
+        self.entries_until(aabb.max.x)
+            .iter()
+            .filter(move |entry| entry.aabb.intersects(&aabb))
+            .map(|entry| (entry.entity, entry.groups))
+    }
+
+    /// Colliders whose bounds the ray passes through up to the maximal time of impact, with their
+    /// collision groups
+    pub fn query_ray(
+        &self,
+        ray: Ray,
+        max_toi: f32,
+    ) -> impl Iterator<Item = (Entity, CollisionGroups)> + '_ {
+        let max_x = if ray.direction.x > 0.0 {
+            ray.point_at(max_toi).x
+        } else {
+            ray.origin.x
+        };
+        self.entries_until(max_x)
+            .iter()
+            .filter(move |entry| entry.aabb.intersects_ray(&ray, max_toi))
+            .map(|entry| (entry.entity, entry.groups))
+    }
+
+    /// Entries starting left of the coordinate
+    fn entries_until(&self, max_x: f32) -> &[BroadPhaseEntry] {
+        let end = self
+            .entries
+            .partition_point(|entry| entry.aabb.min.x <= max_x);
+        &self.entries[..end]
+    }
+
+    /// Removes all pairs for which the predicate returns `false`
+    pub fn retain_pairs(&mut self, mut predicate: impl FnMut(Entity, Entity) -> bool) {
+        self.pairs
+            .retain(|&(entity1, entity2)| predicate(entity1, entity2));
+    }
+
+    /// Replaces the colliders with the given bounding boxes and collision groups and updates the
+    /// pairs
+    pub fn update(&mut self, bounds: impl IntoIterator<Item = (Entity, Aabb, CollisionGroups)>) {
+        self.entries.clear();
+        self.entries.extend(
+            bounds
+                .into_iter()
+                .map(|(entity, aabb, groups)| BroadPhaseEntry {
+                    entity,
+                    aabb,
+                    groups,
+                }),
+        );
+        self.entries
+            .sort_by(|e1, e2| e1.aabb.min.x.total_cmp(&e2.aabb.min.x));
+
+        self.pairs.clear();
+        for (i, entry) in self.entries.iter().enumerate() {
+            for other in self.entries[i + 1..]
+                .iter()
+                // All following entries start right of this one
+                .take_while(|other| other.aabb.min.x <= entry.aabb.max.x)
+            {
+                if other.aabb.intersects(&entry.aabb) && other.groups.interacts_with(&entry.groups)
+                {
+                    self.pairs.push((entry.entity, other.entity));
+                }
+            }
+        }
+    }
+}
+
+/// Updates the broad phase with the current bounds of all colliders
+pub fn update_broad_phase(
+    mut broad_phase: ResMut<BroadPhase>,
+    query: Query<(Entity, &Collider, &Transform, Option<&CollisionGroups>)>,
+) {
+    broad_phase.update(query.iter().map(|(entity, collider, transform, groups)| {
+        (
+            entity,
+            collider.aabb(transform),
+            groups.copied().unwrap_or_default(),
+        )
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::filter::Group;
+
+    use super::*;
+
+    #[test]
+    fn overlapping_pairs() {
+        //  ___________
+        // |  0   _____|_____          ____
+        // |_____|_____|  1  |        | 3  |
+        //       |___________|        |____|
+        //                _____
+        //               |  2  |
+        //               |_____|
+        let bounds = [
+            (
+                Entity::from_raw(0),
+                Aabb::new(Vec2::new(0.0, 1.0), Vec2::new(4.0, 3.0)),
+                CollisionGroups::default(),
+            ),
+            (
+                Entity::from_raw(1),
+                Aabb::new(Vec2::new(2.0, 0.0), Vec2::new(6.0, 2.0)),
+                CollisionGroups::default(),
+            ),
+            (
+                Entity::from_raw(2),
+                Aabb::new(Vec2::new(5.0, -3.0), Vec2::new(7.0, -1.0)),
+                CollisionGroups::default(),
+            ),
+            (
+                Entity::from_raw(3),
+                Aabb::new(Vec2::new(9.0, 0.0), Vec2::new(10.0, 1.0)),
+                CollisionGroups::default(),
+            ),
+        ];
+
+        let mut broad_phase = BroadPhase::default();
+        broad_phase.update(bounds.iter().rev().copied());
+
+        assert_eq!(
+            broad_phase.pairs(),
+            [(Entity::from_raw(0), Entity::from_raw(1))]
+        );
+    }
+
+    #[test]
+    fn touching_bounds_are_a_pair() {
+        let bounds = [
+            (
+                Entity::from_raw(0),
+                Aabb::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)),
+                CollisionGroups::default(),
+            ),
+            (
+                Entity::from_raw(1),
+                Aabb::new(Vec2::new(1.0, 1.0), Vec2::new(2.0, 2.0)),
+                CollisionGroups::default(),
+            ),
+        ];
+
+        let mut broad_phase = BroadPhase::default();
+        broad_phase.update(bounds);
+
+        assert_eq!(broad_phase.pairs().len(), 1);
+    }
+
+    #[test]
+    fn query_bounds() {
+        let bounds = [
+            (
+                Entity::from_raw(0),
+                Aabb::new(Vec2::new(0.0, 0.0), Vec2::new(2.0, 2.0)),
+                CollisionGroups::default(),
+            ),
+            (
+                Entity::from_raw(1),
+                Aabb::new(Vec2::new(3.0, 0.0), Vec2::new(4.0, 1.0)),
+                CollisionGroups::default(),
+            ),
+            (
+                Entity::from_raw(2),
+                Aabb::new(Vec2::new(1.0, 3.0), Vec2::new(5.0, 4.0)),
+                CollisionGroups::default(),
+            ),
+        ];
+        let mut broad_phase = BroadPhase::default();
+        broad_phase.update(bounds);
+
+        let entities = |query: &mut dyn Iterator<Item = (Entity, CollisionGroups)>| {
+            let mut entities: Vec<u32> = query.map(|(entity, _)| entity.index()).collect();
+            entities.sort();
+            entities
+        };
+        assert_eq!(
+            entities(&mut broad_phase.query_aabb(Aabb::new(Vec2::splat(1.5), Vec2::splat(3.5)))),
+            [0, 2]
+        );
+        assert!(entities(
+            &mut broad_phase.query_aabb(Aabb::new(Vec2::new(-2.0, 0.0), Vec2::new(-1.0, 5.0)))
+        )
+        .is_empty());
+
+        let ray = Ray::new(Vec2::new(6.0, 0.5), -Vec2::X);
+        assert_eq!(entities(&mut broad_phase.query_ray(ray, f32::MAX)), [0, 1]);
+        assert_eq!(entities(&mut broad_phase.query_ray(ray, 2.5)), [1]);
+    }
+
+    #[test]
+    fn non_interacting_groups_are_no_pair() {
+        let aabb = Aabb::new(Vec2::ZERO, Vec2::ONE);
+        let enemy = CollisionGroups::new(Group::GROUP_2, Group::ALL ^ Group::GROUP_2);
+        let bounds = [
+            (Entity::from_raw(0), aabb, enemy),
+            (Entity::from_raw(1), aabb, enemy),
+            (Entity::from_raw(2), aabb, CollisionGroups::default()),
+        ];
+
+        let mut broad_phase = BroadPhase::default();
+        broad_phase.update(bounds);
+        assert_eq!(broad_phase.pairs().len(), 2);
+
+        broad_phase.retain_pairs(|entity1, entity2| {
+            entity1 != Entity::from_raw(0) && entity2 != Entity::from_raw(0)
+        });
+        assert_eq!(
+            broad_phase.pairs(),
+            [(Entity::from_raw(1), Entity::from_raw(2))]
+        );
+    }
+}
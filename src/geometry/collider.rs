@@ -1,6 +1,18 @@
 use bevy::prelude::*;
 
-use super::shape::Shape;
+use super::{
+    aabb::Aabb,
+    shape::{convex_polygon::ConvexPolygonError, Shape},
+    transform::Transform2d,
+};
+
+/// Marks a collider which only detects overlaps.
+///
+/// Overlaps are reported via [`Collider::collided`] and the collision events, but the bodies are
+/// not pushed apart.
+#[derive(Component, Default, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct Sensor;
 
 #[derive(Component)]
 pub struct Collider {
@@ -16,6 +28,9 @@ impl Collider {
         }
     }
 
+    /// # Panics
+    ///
+    /// If the vertices do not describe a convex polygon, see [`Shape::convex_polygon`]
     pub fn convex_polygon(vertices: Vec<Vec2>) -> Self {
         Self {
             shape: Shape::convex_polygon(vertices),
@@ -23,6 +38,76 @@ impl Collider {
         }
     }
 
+    /// Convex polygon through the vertices, see [`Shape::try_convex_polygon`]
+    pub fn try_convex_polygon(vertices: Vec<Vec2>) -> Result<Self, ConvexPolygonError> {
+        Ok(Self {
+            shape: Shape::try_convex_polygon(vertices)?,
+            collided: false,
+        })
+    }
+
+    /// Convex hull of the points, see [`ConvexPolygon::try_from_hull`](super::shape::convex_polygon::ConvexPolygon::try_from_hull)
+    pub fn convex_hull(points: &[Vec2]) -> Result<Self, ConvexPolygonError> {
+        Ok(Self {
+            shape: Shape::convex_hull(points)?,
+            collided: false,
+        })
+    }
+
+    /// Collider of a simple polygon, which may be concave. See [`Shape::concave_polygon`].
+    pub fn concave_polygon(vertices: Vec<Vec2>) -> Result<Self, ConvexPolygonError> {
+        Ok(Self {
+            shape: Shape::concave_polygon(vertices)?,
+            collided: false,
+        })
+    }
+
+    pub fn capsule(a: Vec2, b: Vec2, radius: f32) -> Self {
+        Self {
+            shape: Shape::capsule(a, b, radius),
+            collided: false,
+        }
+    }
+
+    pub fn segment(a: Vec2, b: Vec2) -> Self {
+        Self {
+            shape: Shape::segment(a, b),
+            collided: false,
+        }
+    }
+
+    pub fn polyline(vertices: Vec<Vec2>) -> Self {
+        Self {
+            shape: Shape::polyline(vertices),
+            collided: false,
+        }
+    }
+
+    pub fn one_sided_polyline(vertices: Vec<Vec2>) -> Self {
+        Self {
+            shape: Shape::one_sided_polyline(vertices),
+            collided: false,
+        }
+    }
+
+    pub fn heightfield(heights: Vec<f32>, scale: f32) -> Self {
+        Self {
+            shape: Shape::heightfield(heights, scale),
+            collided: false,
+        }
+    }
+
+    /// Collider made of several shapes placed relative to it
+    pub fn compound(shapes: Vec<(Transform2d, Shape)>) -> Self {
+        Self {
+            shape: Shape::compound(shapes),
+            collided: false,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If the width or height is zero
     pub fn rect(width: f32, height: f32) -> Self {
         Self {
             shape: Shape::rect(width, height),
@@ -30,10 +115,34 @@ impl Collider {
         }
     }
 
+    /// Rectangle centered at the origin, see [`Shape::try_rect`]
+    pub fn try_rect(width: f32, height: f32) -> Result<Self, ConvexPolygonError> {
+        Ok(Self {
+            shape: Shape::try_rect(width, height)?,
+            collided: false,
+        })
+    }
+
+    /// # Panics
+    ///
+    /// If there are less than three sides or the radius is zero
     pub fn regular_polygon(radius: f32, sides: usize) -> Self {
         Self {
             shape: Shape::regular_polygon(radius, sides),
             collided: false,
         }
     }
+
+    /// Regular polygon with a corner at the top, see [`Shape::try_regular_polygon`]
+    pub fn try_regular_polygon(radius: f32, sides: usize) -> Result<Self, ConvexPolygonError> {
+        Ok(Self {
+            shape: Shape::try_regular_polygon(radius, sides)?,
+            collided: false,
+        })
+    }
+
+    /// Bounding box of the collider in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        self.shape.aabb(transform)
+    }
 }
@@ -1,12 +1,55 @@
-use bevy::{math::Vec2Swizzles, prelude::*};
-use bevy_prototype_debug_lines::{DebugLines, DebugLinesPlugin, DebugShapes};
+use bevy::prelude::*;
 use itertools::Itertools;
 
-use super::shape::{circle::Circle, convex_polygon::ConvexPolygon};
+use crate::math::{closest_point_on_segment, closest_points_between_segments};
 
+use super::{
+    aabb::Aabb,
+    gjk::collide_convex,
+    shape::{
+        capsule::Capsule, circle::Circle, convex_polygon::ConvexPolygon, heightfield::Heightfield,
+        polyline::Polyline, segment::Segment, Shape,
+    },
+    transform::Transform2d,
+};
+
+/// Minimal cosine of the angle between a collision normal and a face normal to count as contact
+/// with the face instead of one of its corners
+const FACE_CONTACT_TOLERANCE: f32 = 0.999;
+
+/// Point where two shapes touch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContactPoint {
+    /// Position in world coordinates
+    pub point: Vec2,
+    /// Direction in which the shapes are pushed apart at this point, pointing from the first to the
+    /// second shape
+    pub normal: Vec2,
+    /// Penetration depth along the normal
+    pub depth: f32,
+}
+
+/// Contact manifold of two colliding shapes
+#[derive(Clone, Debug, PartialEq)]
 pub struct CollisionResponse {
+    /// Direction of the deepest collision, pointing from the first to the second shape
     pub normal: Vec2,
+    /// Deepest penetration along the normal
     pub depth: f32,
+    /// Points where the shapes touch, up to two per direction. Compound shapes and chains can
+    /// touch in several directions at once.
+    pub contacts: Vec<ContactPoint>,
+}
+
+impl CollisionResponse {
+    /// The same collision seen from the second shape
+    pub fn flipped(mut self) -> Self {
+        self.normal = -self.normal;
+        for contact in &mut self.contacts {
+            contact.normal = -contact.normal;
+        }
+        self
+    }
 }
 
 pub trait CollisionWith<T> {
@@ -15,40 +58,139 @@ pub trait CollisionWith<T> {
         other: &T,
         transform: &Transform,
         other_transform: &Transform,
-        lines: &mut ResMut<DebugLines>,
     ) -> Option<CollisionResponse>;
 }
 
+impl CollisionWith<Shape> for Shape {
+    fn collides(
+        &self,
+        other: &Shape,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        macro_rules! dispatch {
+            ($shape:expr) => {
+                match other {
+                    Shape::Circle(other) => $shape.collides(other, transform, other_transform),
+                    Shape::ConvexPolygon(other) => {
+                        $shape.collides(other, transform, other_transform)
+                    }
+                    Shape::Capsule(other) => $shape.collides(other, transform, other_transform),
+                    Shape::Segment(other) => $shape.collides(other, transform, other_transform),
+                    Shape::Polyline(other) => $shape.collides(other, transform, other_transform),
+                    Shape::Heightfield(other) => $shape.collides(other, transform, other_transform),
+                    // Compound shapes are split up before, shapes without a hand-written collision
+                    // are convex
+                    _ => collide_support_maps(self, other, transform, other_transform),
+                }
+            };
+        }
+
+        match (self, other) {
+            (Shape::Compound(shapes), _) => {
+                let other_aabb = other.aabb(other_transform);
+                collide_compound(shapes, transform, &other_aabb, |shape, transform| {
+                    shape.collides(other, transform, other_transform)
+                })
+            }
+            (_, Shape::Compound(shapes)) => {
+                let aabb = self.aabb(transform);
+                collide_compound(shapes, other_transform, &aabb, |shape, other_transform| {
+                    self.collides(shape, transform, other_transform)
+                })
+            }
+            (Shape::Circle(shape), _) => dispatch!(shape),
+            (Shape::ConvexPolygon(shape), _) => dispatch!(shape),
+            (Shape::Capsule(shape), _) => dispatch!(shape),
+            (Shape::Segment(shape), _) => dispatch!(shape),
+            (Shape::Polyline(shape), _) => dispatch!(shape),
+            (Shape::Heightfield(shape), _) => dispatch!(shape),
+            // Only reached by shapes added without hand-written collisions
+            #[allow(unreachable_patterns)]
+            _ => collide_support_maps(self, other, transform, other_transform),
+        }
+    }
+}
+
+/// Contact manifold of two convex shapes with a single contact point, see [`collide_convex`].
+///
+/// Used for pairs of shapes without a hand-written collision, so new convex shapes only need a
+/// [`SupportMap`](super::gjk::SupportMap).
+fn collide_support_maps(
+    shape: &Shape,
+    other: &Shape,
+    transform: &Transform,
+    other_transform: &Transform,
+) -> Option<CollisionResponse> {
+    collide_convex(
+        shape.as_support_map()?,
+        transform,
+        other.as_support_map()?,
+        other_transform,
+    )
+}
+
+/// Implements the collision of `$shape` with `$other` by swapping the shapes of the existing
+/// collision of `$other` with `$shape`
+macro_rules! flipped_collision {
+    ($shape:ty, $other:ty) => {
+        impl CollisionWith<$other> for $shape {
+            fn collides(
+                &self,
+                other: &$other,
+                transform: &Transform,
+                other_transform: &Transform,
+            ) -> Option<CollisionResponse> {
+                other
+                    .collides(self, other_transform, transform)
+                    .map(CollisionResponse::flipped)
+            }
+        }
+    };
+}
+
+flipped_collision!(ConvexPolygon, Circle);
+flipped_collision!(Circle, Capsule);
+flipped_collision!(ConvexPolygon, Capsule);
+flipped_collision!(Circle, Segment);
+flipped_collision!(ConvexPolygon, Segment);
+flipped_collision!(Capsule, Segment);
+flipped_collision!(Circle, Polyline);
+flipped_collision!(ConvexPolygon, Polyline);
+flipped_collision!(Capsule, Polyline);
+flipped_collision!(Segment, Polyline);
+flipped_collision!(Circle, Heightfield);
+flipped_collision!(ConvexPolygon, Heightfield);
+flipped_collision!(Capsule, Heightfield);
+flipped_collision!(Segment, Heightfield);
+flipped_collision!(Polyline, Heightfield);
+
 impl CollisionWith<Circle> for Circle {
     fn collides(
         &self,
         other: &Circle,
         transform: &Transform,
         other_transform: &Transform,
-        lines: &mut ResMut<DebugLines>,
     ) -> Option<CollisionResponse> {
         let normal = (other_transform.translation - transform.translation).truncate();
         let distance = normal.length();
-        let radii = self.radius() + other.radius();
-
-        // let end = transform.translation.truncate() + (1.0 + self.radius()) * normal.normalize();
-        // lines.line_colored(transform.translation, end.extend(0.0), 0.0, Color::RED);
-        // let end =
-        //     other_transform.translation.truncate() + (1.0 + other.radius()) * -normal.normalize();
-        // lines.line_colored(
-        //     other_transform.translation,
-        //     end.extend(0.0),
-        //     0.0,
-        //     Color::RED,
-        // );
+        let radii = self.world_radius(transform) + other.world_radius(other_transform);
 
         if distance >= radii {
             return None;
         }
 
+        let normal = normal.normalize_or_zero();
+        let depth = radii - distance;
         Some(CollisionResponse {
-            normal: normal.normalize(),
-            depth: radii - distance,
+            normal,
+            depth,
+            contacts: vec![ContactPoint {
+                point: transform.translation.truncate()
+                    + normal * (self.world_radius(transform) - depth / 2.0),
+                normal,
+                depth,
+            }],
         })
     }
 }
@@ -59,210 +201,1582 @@ impl CollisionWith<ConvexPolygon> for Circle {
         other: &ConvexPolygon,
         transform: &Transform,
         other_transform: &Transform,
-        lines: &mut ResMut<DebugLines>,
     ) -> Option<CollisionResponse> {
-        let other_vertices: Vec<Vec2> = other
-            .vertices()
-            .iter()
-            .map(|&v| other_transform.transform_point(v.extend(0.0)).truncate())
-            .collect();
+        let center = transform.translation.truncate();
+        let polygon = WorldPolygon::new(other, other_transform);
+        let vertices = &polygon.vertices;
 
-        let mut normals: Vec<(Vec2, Vec2)> = other_vertices
+        // Side of the polygon closest to the circle center
+        let ((start, end), separation) = polygon
+            .axes
             .iter()
-            .circular_tuple_windows() // Get all sides (wrap last point and first)
-            .map(|(&p1, &p2)| {
-                (
-                    p1 + (p2 - p1) / 2.0,
-                    (p1 - p2).yx().normalize() * Vec2::new(1.0, -1.0),
-                )
-            }) // Calculate normal
-            .unique_by(|(_, normal)| {
-                let x_axis = Vec2::X;
-                let det = normal.x * x_axis.y - normal.y * x_axis.x;
-                let mut angle = normal.dot(Vec2::X).atan2(det).to_degrees();
-                if angle < 0.0 {
-                    angle += 360.0;
-                }
-                let angle = (angle * 100.0) as i32 % 18000;
-                angle
+            .map(|&(start, end)| {
+                let separation = polygon.normals[start].dot(center - vertices[start]);
+                ((start, end), separation)
             })
-            .collect();
-        // Connection from circle center to closest edge of polygon
-        normals.push((
-            transform.translation.truncate(),
-            other_vertices
-                .iter()
-                .map(|&vert| vert - transform.translation.truncate())
-                .min_by(|x, y| x.length().total_cmp(&y.length()))
-                .unwrap()
-                .normalize(),
-        ));
-
-        // for (start, normal) in &normals {
-        //     let end = *start + (1.0 + self.radius()) * normal.normalize();
-        //     lines.line_colored(start.extend(0.0), end.extend(0.0), 0.0, Color::RED);
-        // }
-
-        let mut response_depth = f32::MAX;
-        let mut response_normal = Vec2::ZERO;
-
-        for (start, normal) in &normals {
-            // Separating Axis Theorem (SAT)
-            let circle_proj = transform.translation.truncate().dot(*normal);
-            let self_min = circle_proj - self.radius();
-            let self_max = circle_proj + self.radius();
-
-            let (other_min, other_max) =
-                match other_vertices.iter().map(|vert| vert.dot(*normal)).minmax() {
-                    itertools::MinMaxResult::MinMax(min, max) => (min, max),
-                    _ => panic!("We cannot have single point polygons"),
-                };
-
-            let gap = self_min >= other_max || other_min >= self_max;
-
-            // let end = *start + normal.normalize();
-            // lines.line_colored(
-            //     start.extend(0.0),
-            //     end.extend(0.0),
-            //     0.0,
-            //     if gap { Color::GREEN } else { Color::RED },
-            // );
-
-            if gap {
+            .max_by(|(_, s1), (_, s2)| s1.total_cmp(s2))
+            .expect("We cannot have empty polygons");
+        if separation >= self.world_radius(transform) {
+            return None;
+        }
+
+        let (v1, v2) = (vertices[start], vertices[end]);
+        // Normal pointing from the polygon to the circle
+        let (normal, depth, point) = if separation <= 0.0 {
+            // Center inside of the polygon
+            let normal = polygon.normals[start];
+            (
+                normal,
+                self.world_radius(transform) - separation,
+                center - normal * separation,
+            )
+        } else {
+            let closest = closest_point_on_segment(center, v1, v2);
+            let distance = center.distance(closest);
+            if distance >= self.world_radius(transform) {
                 return None;
             }
+            (
+                (center - closest) / distance,
+                self.world_radius(transform) - distance,
+                closest,
+            )
+        };
 
-            let axis_depth = (other_max - self_min).min(self_max - other_min);
-            if axis_depth < response_depth {
-                response_depth = axis_depth;
-                response_normal = *normal;
-            }
-        }
+        Some(CollisionResponse {
+            normal: -normal,
+            depth,
+            contacts: vec![ContactPoint {
+                point,
+                normal: -normal,
+                depth,
+            }],
+        })
+    }
+}
 
-        // Normal is not always pointing in direction 'self to other'
-        let direction = other_transform.translation.truncate() - transform.translation.truncate();
-        if direction.dot(response_normal) < 0.0 {
-            // Pointing in opposite directions
-            response_normal *= -1.0;
-        }
+impl CollisionWith<ConvexPolygon> for ConvexPolygon {
+    fn collides(
+        &self,
+        other: &ConvexPolygon,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_rounded_polygons(
+            WorldPolygon::new(self, transform).as_ref(),
+            0.0,
+            WorldPolygon::new(other, other_transform).as_ref(),
+            0.0,
+        )
+    }
+}
 
-        Some(CollisionResponse {
-            normal: response_normal,
-            depth: response_depth,
+impl CollisionWith<Circle> for Capsule {
+    fn collides(
+        &self,
+        other: &Circle,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let [a, b] = WorldSegment::from_local(self.a(), self.b(), transform).vertices;
+        collide_rounded_segment_circle(
+            a,
+            b,
+            self.world_radius(transform),
+            other_transform.translation.truncate(),
+            other.world_radius(other_transform),
+        )
+    }
+}
+
+impl CollisionWith<ConvexPolygon> for Capsule {
+    fn collides(
+        &self,
+        other: &ConvexPolygon,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_rounded_polygons(
+            WorldSegment::from_local(self.a(), self.b(), transform).as_ref(),
+            self.world_radius(transform),
+            WorldPolygon::new(other, other_transform).as_ref(),
+            0.0,
+        )
+    }
+}
+
+impl CollisionWith<Capsule> for Capsule {
+    fn collides(
+        &self,
+        other: &Capsule,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_rounded_polygons(
+            WorldSegment::from_local(self.a(), self.b(), transform).as_ref(),
+            self.world_radius(transform),
+            WorldSegment::from_local(other.a(), other.b(), other_transform).as_ref(),
+            other.world_radius(other_transform),
+        )
+    }
+}
+
+impl CollisionWith<Circle> for Segment {
+    fn collides(
+        &self,
+        other: &Circle,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let [a, b] = WorldSegment::from_local(self.a(), self.b(), transform).vertices;
+        collide_rounded_segment_circle(
+            a,
+            b,
+            0.0,
+            other_transform.translation.truncate(),
+            other.world_radius(other_transform),
+        )
+    }
+}
+
+impl CollisionWith<ConvexPolygon> for Segment {
+    fn collides(
+        &self,
+        other: &ConvexPolygon,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_rounded_polygons(
+            WorldSegment::from_local(self.a(), self.b(), transform).as_ref(),
+            0.0,
+            WorldPolygon::new(other, other_transform).as_ref(),
+            0.0,
+        )
+    }
+}
+
+impl CollisionWith<Capsule> for Segment {
+    fn collides(
+        &self,
+        other: &Capsule,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_rounded_polygons(
+            WorldSegment::from_local(self.a(), self.b(), transform).as_ref(),
+            0.0,
+            WorldSegment::from_local(other.a(), other.b(), other_transform).as_ref(),
+            other.world_radius(other_transform),
+        )
+    }
+}
+
+impl CollisionWith<Segment> for Segment {
+    fn collides(
+        &self,
+        other: &Segment,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_rounded_polygons(
+            WorldSegment::from_local(self.a(), self.b(), transform).as_ref(),
+            0.0,
+            WorldSegment::from_local(other.a(), other.b(), other_transform).as_ref(),
+            0.0,
+        )
+    }
+}
+
+impl CollisionWith<Circle> for Polyline {
+    fn collides(
+        &self,
+        other: &Circle,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let center = other_transform.translation.truncate();
+        collide_polyline(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_rounded_segment_circle(a, b, 0.0, center, other.world_radius(other_transform))
+        })
+    }
+}
+
+impl CollisionWith<ConvexPolygon> for Polyline {
+    fn collides(
+        &self,
+        other: &ConvexPolygon,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let polygon = WorldPolygon::new(other, other_transform);
+        collide_polyline(
+            self,
+            transform,
+            &Aabb::from_points(polygon.vertices.clone()),
+            |a, b| {
+                collide_rounded_polygons(
+                    WorldSegment::new(a, b).as_ref(),
+                    0.0,
+                    polygon.as_ref(),
+                    0.0,
+                )
+            },
+        )
+    }
+}
+
+impl CollisionWith<Capsule> for Polyline {
+    fn collides(
+        &self,
+        other: &Capsule,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let segment = WorldSegment::from_local(other.a(), other.b(), other_transform);
+        collide_polyline(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_rounded_polygons(
+                WorldSegment::new(a, b).as_ref(),
+                0.0,
+                segment.as_ref(),
+                other.world_radius(other_transform),
+            )
+        })
+    }
+}
+
+impl CollisionWith<Segment> for Polyline {
+    fn collides(
+        &self,
+        other: &Segment,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let segment = WorldSegment::from_local(other.a(), other.b(), other_transform);
+        collide_polyline(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_rounded_polygons(WorldSegment::new(a, b).as_ref(), 0.0, segment.as_ref(), 0.0)
         })
     }
 }
 
-impl CollisionWith<Circle> for ConvexPolygon {
+impl CollisionWith<Polyline> for Polyline {
+    fn collides(
+        &self,
+        other: &Polyline,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_polyline(self, transform, &other.aabb(other_transform), |a, b| {
+            // Seen from the segments of the other chain, so their ghost vertices are respected too
+            collide_polyline(
+                other,
+                other_transform,
+                &Aabb::from_points([a, b]),
+                |c, d| {
+                    collide_rounded_polygons(
+                        WorldSegment::new(c, d).as_ref(),
+                        0.0,
+                        WorldSegment::new(a, b).as_ref(),
+                        0.0,
+                    )
+                },
+            )
+            .map(CollisionResponse::flipped)
+        })
+    }
+}
+
+impl CollisionWith<Circle> for Heightfield {
     fn collides(
         &self,
         other: &Circle,
         transform: &Transform,
         other_transform: &Transform,
-        lines: &mut ResMut<DebugLines>,
     ) -> Option<CollisionResponse> {
-        let mut collision_response = other.collides(self, other_transform, transform, lines);
-        if let Some(mut response) = collision_response {
-            response.normal *= -1.0;
-            collision_response = Some(response);
-        }
-        collision_response
+        let center = other_transform.translation.truncate();
+        collide_heightfield(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_rounded_segment_circle(a, b, 0.0, center, other.world_radius(other_transform))
+        })
     }
 }
 
-impl CollisionWith<ConvexPolygon> for ConvexPolygon {
+impl CollisionWith<ConvexPolygon> for Heightfield {
     fn collides(
         &self,
         other: &ConvexPolygon,
         transform: &Transform,
         other_transform: &Transform,
-        lines: &mut ResMut<DebugLines>,
     ) -> Option<CollisionResponse> {
-        let self_vertices: Vec<Vec2> = self
+        let polygon = WorldPolygon::new(other, other_transform);
+        collide_heightfield(
+            self,
+            transform,
+            &Aabb::from_points(polygon.vertices.clone()),
+            |a, b| {
+                collide_rounded_polygons(
+                    WorldSegment::new(a, b).as_ref(),
+                    0.0,
+                    polygon.as_ref(),
+                    0.0,
+                )
+            },
+        )
+    }
+}
+
+impl CollisionWith<Capsule> for Heightfield {
+    fn collides(
+        &self,
+        other: &Capsule,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let segment = WorldSegment::from_local(other.a(), other.b(), other_transform);
+        collide_heightfield(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_rounded_polygons(
+                WorldSegment::new(a, b).as_ref(),
+                0.0,
+                segment.as_ref(),
+                other.world_radius(other_transform),
+            )
+        })
+    }
+}
+
+impl CollisionWith<Segment> for Heightfield {
+    fn collides(
+        &self,
+        other: &Segment,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        let segment = WorldSegment::from_local(other.a(), other.b(), other_transform);
+        collide_heightfield(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_rounded_polygons(WorldSegment::new(a, b).as_ref(), 0.0, segment.as_ref(), 0.0)
+        })
+    }
+}
+
+impl CollisionWith<Polyline> for Heightfield {
+    fn collides(
+        &self,
+        other: &Polyline,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_heightfield(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_polyline(
+                other,
+                other_transform,
+                &Aabb::from_points([a, b]),
+                |c, d| {
+                    collide_rounded_polygons(
+                        WorldSegment::new(c, d).as_ref(),
+                        0.0,
+                        WorldSegment::new(a, b).as_ref(),
+                        0.0,
+                    )
+                },
+            )
+            .map(CollisionResponse::flipped)
+        })
+    }
+}
+
+impl CollisionWith<Heightfield> for Heightfield {
+    fn collides(
+        &self,
+        other: &Heightfield,
+        transform: &Transform,
+        other_transform: &Transform,
+    ) -> Option<CollisionResponse> {
+        collide_heightfield(self, transform, &other.aabb(other_transform), |a, b| {
+            collide_heightfield(
+                other,
+                other_transform,
+                &Aabb::from_points([a, b]),
+                |c, d| {
+                    collide_rounded_polygons(
+                        WorldSegment::new(c, d).as_ref(),
+                        0.0,
+                        WorldSegment::new(a, b).as_ref(),
+                        0.0,
+                    )
+                },
+            )
+            .map(CollisionResponse::flipped)
+        })
+    }
+}
+
+/// Contact manifold of a segment from `a` to `b` rounded by `radius` with a circle
+fn collide_rounded_segment_circle(
+    a: Vec2,
+    b: Vec2,
+    radius: f32,
+    center: Vec2,
+    circle_radius: f32,
+) -> Option<CollisionResponse> {
+    let closest = closest_point_on_segment(center, a, b);
+
+    let normal = center - closest;
+    let distance = normal.length();
+    let radii = radius + circle_radius;
+    if distance >= radii {
+        return None;
+    }
+
+    let normal = if distance > 0.0 {
+        normal / distance
+    } else {
+        // Circle center on the segment, push it out sideways
+        outward_normal(a, b)
+    };
+    let depth = radii - distance;
+    Some(CollisionResponse {
+        normal,
+        depth,
+        contacts: vec![ContactPoint {
+            point: closest + normal * (radius - depth / 2.0),
+            normal,
+            depth,
+        }],
+    })
+}
+
+/// Contact manifold of a polyline with a shape with the given bounding box, see [`collide_chain`]
+fn collide_polyline(
+    polyline: &Polyline,
+    transform: &Transform,
+    other_aabb: &Aabb,
+    collide_segment: impl Fn(Vec2, Vec2) -> Option<CollisionResponse>,
+) -> Option<CollisionResponse> {
+    let vertices: Vec<Vec2> = polyline
+        .vertices()
+        .iter()
+        .map(|&v| transform.transform_point(v.extend(0.0)).truncate())
+        .collect();
+    collide_chain(
+        &vertices,
+        polyline.is_one_sided(),
+        other_aabb,
+        collide_segment,
+    )
+}
+
+/// Contact manifold of a heightfield with a shape with the given bounding box.
+///
+/// Only the cells below the bounding box are tested, see [`collide_chain`].
+fn collide_heightfield(
+    heightfield: &Heightfield,
+    transform: &Transform,
+    other_aabb: &Aabb,
+    collide_segment: impl Fn(Vec2, Vec2) -> Option<CollisionResponse>,
+) -> Option<CollisionResponse> {
+    let inverse = transform.compute_affine().inverse();
+    let local_aabb = Aabb::from_points(
+        [
+            other_aabb.min,
+            Vec2::new(other_aabb.max.x, other_aabb.min.y),
+            other_aabb.max,
+            Vec2::new(other_aabb.min.x, other_aabb.max.y),
+        ]
+        .map(|v| inverse.transform_point3(v.extend(0.0)).truncate()),
+    );
+    let cells = heightfield.cells_between(local_aabb.min.x, local_aabb.max.x);
+    if cells.is_empty() {
+        return None;
+    }
+
+    // Samples of the cells and their neighbors as ghost vertices. From right to left, so the top
+    // of the surface is the colliding right side of the chain.
+    let samples = cells.start.saturating_sub(1)..(cells.end + 2).min(heightfield.heights().len());
+    let vertices: Vec<Vec2> = samples
+        .rev()
+        .map(|i| {
+            transform
+                .transform_point(heightfield.vertex(i).extend(0.0))
+                .truncate()
+        })
+        .collect();
+    collide_chain(&vertices, true, other_aabb, collide_segment)
+}
+
+/// Contact manifold of a chain of segments through the vertices in world coordinates with a
+/// shape with the given bounding box.
+///
+/// `collide_segment` returns the manifold of a single segment of the chain with the shape.
+/// Collisions with the back of one-sided chains and at inner vertices, which the neighboring
+/// segment already covers, are dropped. The remaining ones are combined with
+/// [`merge_responses`].
+fn collide_chain(
+    vertices: &[Vec2],
+    one_sided: bool,
+    other_aabb: &Aabb,
+    collide_segment: impl Fn(Vec2, Vec2) -> Option<CollisionResponse>,
+) -> Option<CollisionResponse> {
+    let responses: Vec<CollisionResponse> = (0..vertices.len().saturating_sub(1))
+        .filter(|&i| Aabb::from_points([vertices[i], vertices[i + 1]]).intersects(other_aabb))
+        .filter_map(|i| {
+            collide_segment(vertices[i], vertices[i + 1])
+                .filter(|response| chain_contact_allowed(vertices, i, one_sided, response))
+        })
+        .collect();
+
+    merge_responses(&responses)
+}
+
+/// Contact manifold of the shapes of a compound with a shape with the given bounding box.
+///
+/// `collide_shape` returns the manifold of a single shape of the compound with its world
+/// transform with the other shape. The manifolds of all touching shapes are combined with
+/// [`merge_responses`].
+fn collide_compound(
+    shapes: &[(Transform2d, Shape)],
+    transform: &Transform,
+    other_aabb: &Aabb,
+    collide_shape: impl Fn(&Shape, &Transform) -> Option<CollisionResponse>,
+) -> Option<CollisionResponse> {
+    let responses: Vec<CollisionResponse> = shapes
+        .iter()
+        .filter_map(|(child_transform, shape)| {
+            let transform = transform.mul_transform((*child_transform).into());
+            if shape.aabb(&transform).intersects(other_aabb) {
+                collide_shape(shape, &transform)
+            } else {
+                None
+            }
+        })
+        .collect();
+    merge_responses(&responses)
+}
+
+/// Combines the manifolds of several parts of a shape into one.
+///
+/// The deepest collision provides the normal and depth. The contacts of all collisions are grouped
+/// by their direction and of every group the outermost contacts along the surface are kept.
+fn merge_responses(responses: &[CollisionResponse]) -> Option<CollisionResponse> {
+    let deepest = responses
+        .iter()
+        .max_by(|r1, r2| r1.depth.total_cmp(&r2.depth))?;
+
+    let mut groups: Vec<Vec<ContactPoint>> = Vec::new();
+    for &contact in responses.iter().flat_map(|response| &response.contacts) {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].normal.dot(contact.normal) >= FACE_CONTACT_TOLERANCE)
+        {
+            Some(group) => group.push(contact),
+            None => groups.push(vec![contact]),
+        }
+    }
+    let contacts = groups
+        .into_iter()
+        .flat_map(|group| {
+            let tangent = group[0].normal.perp();
+            let (first, last) = group
+                .into_iter()
+                .minmax_by(|c1, c2| tangent.dot(c1.point).total_cmp(&tangent.dot(c2.point)))
+                .into_option()
+                .expect("Groups have at least one contact");
+            if first == last {
+                vec![first]
+            } else {
+                vec![first, last]
+            }
+        })
+        .collect();
+
+    Some(CollisionResponse {
+        normal: deepest.normal,
+        depth: deepest.depth,
+        contacts,
+    })
+}
+
+/// Whether the collision of segment `i` of the chain through the vertices is not prevented by the
+/// one-sidedness or the ghost vertices.
+///
+/// The normal of the collision has to point away from the segment.
+fn chain_contact_allowed(
+    vertices: &[Vec2],
+    i: usize,
+    one_sided: bool,
+    response: &CollisionResponse,
+) -> bool {
+    let (a, b) = (vertices[i], vertices[i + 1]);
+    let facing = outward_normal(a, b).dot(response.normal);
+    if one_sided && facing <= -FACE_CONTACT_TOLERANCE {
+        return false;
+    }
+    if facing.abs() >= FACE_CONTACT_TOLERANCE {
+        return true;
+    }
+
+    // Collision around one of the end points of the segment
+    let contact = response
+        .contacts
+        .iter()
+        .map(|contact| contact.point)
+        .sum::<Vec2>()
+        / response.contacts.len() as f32;
+    let (previous, vertex, next) = if (contact - a).dot(b - a) < 0.5 * (b - a).length_squared() {
+        if i == 0 {
+            return !one_sided || facing > 0.0;
+        }
+        (vertices[i - 1], a, b)
+    } else {
+        if i + 2 >= vertices.len() {
+            return !one_sided || facing > 0.0;
+        }
+        (a, b, vertices[i + 2])
+    };
+
+    // Side of the chain the other shape is on
+    let side = if one_sided || facing > 0.0 { 1.0 } else { -1.0 };
+    // Concave or flat corners are covered by the faces of the two segments, convex corners only
+    // between the normals of the faces
+    let turn = side * (vertex - previous).perp_dot(next - vertex);
+    let normal1 = side * outward_normal(previous, vertex);
+    let normal2 = side * outward_normal(vertex, next);
+    turn > 0.0
+        && side * normal1.perp_dot(response.normal) >= -0.001
+        && side * response.normal.perp_dot(normal2) >= -0.001
+}
+
+/// Contact manifold of two convex polygons which are rounded by the given radii.
+///
+/// Two vertices describe a segment, which makes a capsule if rounded.
+fn collide_rounded_polygons(
+    polygon: PolygonRef<'_>,
+    radius: f32,
+    other: PolygonRef<'_>,
+    other_radius: f32,
+) -> Option<CollisionResponse> {
+    let radii = radius + other_radius;
+
+    // Separating Axis Theorem (SAT)
+    let (self_side, self_separation) = max_separation(polygon, other.vertices);
+    if self_separation >= radii {
+        return None;
+    }
+    let (other_side, other_separation) = max_separation(other, polygon.vertices);
+    if other_separation >= radii {
+        return None;
+    }
+
+    // The polygon with the least penetrating side provides the reference face, the other one
+    // the incident face which gets clipped against it. Prefer our own side for coherence.
+    let flip = other_separation > 0.98 * self_separation + 0.001;
+    let (reference, incident, reference_side, reference_radius, incident_radius) = if flip {
+        (other, polygon, other_side, other_radius, radius)
+    } else {
+        (polygon, other, self_side, radius, other_radius)
+    };
+
+    let (reference_start, reference_end) = reference_side;
+    let (v1, v2) = (
+        reference.vertices[reference_start],
+        reference.vertices[reference_end],
+    );
+    let reference_normal = reference.normals[reference_start];
+
+    let separation = self_separation.max(other_separation);
+    if separation > 0.0 {
+        // Only the rounded borders overlap. Unless the closest features are faces, the shapes
+        // touch in a single point around a corner.
+        let (closest, other_closest) =
+            closest_points_between_polygons(polygon.vertices, other.vertices);
+        let distance = closest.distance(other_closest);
+        if distance >= radii {
+            return None;
+        }
+        if separation < FACE_CONTACT_TOLERANCE * distance {
+            let normal = (other_closest - closest) / distance;
+            let depth = radii - distance;
+            return Some(CollisionResponse {
+                normal,
+                depth,
+                contacts: vec![ContactPoint {
+                    point: other_closest - normal * other_radius,
+                    normal,
+                    depth,
+                }],
+            });
+        }
+    }
+
+    // Side of the incident polygon facing the reference face most
+    let (incident_start, incident_end) = incident
+        .axes
+        .iter()
+        .copied()
+        .min_by(|&(i, _), &(j, _)| {
+            let d1 = incident.normals[i].dot(reference_normal);
+            let d2 = incident.normals[j].dot(reference_normal);
+            d1.total_cmp(&d2)
+        })
+        .expect("We cannot have empty polygons");
+    let i1 = incident.vertices[incident_start];
+    let i2 = incident.vertices[incident_end];
+
+    // Only keep the part of the incident edge next to the reference face
+    let tangent = (v2 - v1).normalize();
+    let (i1, i2) = clip_segment(i1, i2, tangent, tangent.dot(v1))
+        .and_then(|(i1, i2)| clip_segment(i1, i2, -tangent, -tangent.dot(v2)))?;
+
+    let normal = if flip {
+        -reference_normal
+    } else {
+        reference_normal
+    };
+    let contacts: Vec<ContactPoint> = [i1, i2]
+        .into_iter()
+        .map(|point| ContactPoint {
+            point: point - reference_normal * incident_radius,
+            normal,
+            depth: reference_radius + incident_radius - reference_normal.dot(point - v1),
+        })
+        .filter(|contact| contact.depth >= 0.0)
+        .collect();
+    let depth = contacts
+        .iter()
+        .map(|contact| contact.depth)
+        .reduce(f32::max)?;
+
+    Some(CollisionResponse {
+        normal,
+        depth,
+        contacts,
+    })
+}
+
+/// Closest points between the borders of two polygons
+fn closest_points_between_polygons(vertices: &[Vec2], other_vertices: &[Vec2]) -> (Vec2, Vec2) {
+    vertices
+        .iter()
+        .circular_tuple_windows()
+        .flat_map(|(&a1, &b1)| {
+            other_vertices
+                .iter()
+                .circular_tuple_windows()
+                .map(move |(&a2, &b2)| closest_points_between_segments(a1, b1, a2, b2))
+        })
+        .min_by(|(p1, q1), (p2, q2)| {
+            p1.distance_squared(*q1)
+                .total_cmp(&p2.distance_squared(*q2))
+        })
+        .expect("We cannot have empty polygons")
+}
+
+/// Convex polygon in world coordinates, see [`collide_rounded_polygons`]
+#[derive(Clone, Copy)]
+struct PolygonRef<'a> {
+    /// Vertices in counterclockwise order
+    vertices: &'a [Vec2],
+    /// Outward normal of the edge starting at the vertex with the same index
+    normals: &'a [Vec2],
+    /// Straight sides as the indices of their first and last vertex, their normals are tested as
+    /// separating axes
+    axes: &'a [(usize, usize)],
+}
+
+/// Segment in world coordinates, a polygon with two edges facing opposite directions
+struct WorldSegment {
+    vertices: [Vec2; 2],
+    normals: [Vec2; 2],
+}
+
+impl WorldSegment {
+    fn new(a: Vec2, b: Vec2) -> Self {
+        let normal = outward_normal(a, b);
+        Self {
+            vertices: [a, b],
+            normals: [normal, -normal],
+        }
+    }
+
+    /// Segment between the transformed end points
+    fn from_local(a: Vec2, b: Vec2, transform: &Transform) -> Self {
+        let [a, b] = [a, b].map(|v| transform.transform_point(v.extend(0.0)).truncate());
+        Self::new(a, b)
+    }
+
+    fn as_ref(&self) -> PolygonRef<'_> {
+        PolygonRef {
+            vertices: &self.vertices,
+            normals: &self.normals,
+            axes: &[(0, 1), (1, 0)],
+        }
+    }
+}
+
+/// Convex polygon transformed into world coordinates
+struct WorldPolygon {
+    vertices: Vec<Vec2>,
+    normals: Vec<Vec2>,
+    axes: Vec<(usize, usize)>,
+}
+
+impl WorldPolygon {
+    /// Transforms the vertices and the cached normals of the polygon
+    fn new(polygon: &ConvexPolygon, transform: &Transform) -> Self {
+        let mut vertices: Vec<Vec2> = polygon
             .vertices()
             .iter()
             .map(|&v| transform.transform_point(v.extend(0.0)).truncate())
             .collect();
-        let other_vertices: Vec<Vec2> = other
-            .vertices()
+        // Normals are scaled inversely to stay perpendicular to the scaled edges
+        let scale = transform.scale.truncate();
+        let mut normals: Vec<Vec2> = polygon
+            .normals()
             .iter()
-            .map(|&v| other_transform.transform_point(v.extend(0.0)).truncate())
-            .collect();
-
-        let normals: Vec<(Vec2, Vec2)> = self_vertices
-            .iter()
-            .circular_tuple_windows() // Get all sides (wrap last point and first)
-            .chain(other_vertices.iter().circular_tuple_windows()) // Do also for other shape
-            .map(|(&p1, &p2)| (p1 + (p2 - p1) / 2.0, (p1 - p2).yx() * Vec2::new(1.0, -1.0))) // Calculate normal
-            .unique_by(|(_, normal)| {
-                let x_axis = Vec2::X;
-                let det = normal.x * x_axis.y - normal.y * x_axis.x;
-                let mut angle = normal.dot(Vec2::X).atan2(det).to_degrees();
-                if angle < 0.0 {
-                    angle += 360.0;
-                }
-                let angle = (angle * 100.0) as i32 % 18000;
-                angle
+            .map(|&n| {
+                (transform.rotation * (n / scale).extend(0.0))
+                    .truncate()
+                    .normalize()
             })
             .collect();
+        let mut axes = polygon.axes().clone();
 
-        // for (start, normal) in &normals {
-        //     let end = *start + normal.normalize();
-        //     lines.line_colored(start.extend(0.0), end.extend(0.0), 0.0, Color::RED);
-        // }
-
-        let mut response_depth = f32::MAX;
-        let mut response_normal = Vec2::ZERO;
-
-        for (start, normal) in &normals {
-            // Separating Axis Theorem (SAT)
-            let (self_min, self_max) =
-                match self_vertices.iter().map(|vert| vert.dot(*normal)).minmax() {
-                    itertools::MinMaxResult::MinMax(min, max) => (min, max),
-                    _ => panic!("We cannot have single point polygons"),
-                };
-
-            let (other_min, other_max) =
-                match other_vertices.iter().map(|vert| vert.dot(*normal)).minmax() {
-                    itertools::MinMaxResult::MinMax(min, max) => (min, max),
-                    _ => panic!("We cannot have single point polygons"),
-                };
-
-            let gap = self_min >= other_max || other_min >= self_max;
-
-            // let end = *start + normal.normalize();
-            // lines.line_colored(
-            //     start.extend(0.0),
-            //     end.extend(0.0),
-            //     0.0,
-            //     if gap { Color::GREEN } else { Color::RED },
-            // );
-
-            if gap {
-                return None;
-            }
+        if scale.x * scale.y < 0.0 {
+            // Mirroring turns the polygon clockwise. Reversed, edge `i` is the former edge
+            // `n - 2 - i` running the other way and sides start at their former last vertex.
+            let n = vertices.len();
+            let reversed_edge = |i: usize| (2 * n - 2 - i) % n;
+            vertices.reverse();
+            normals = (0..n).map(|i| normals[reversed_edge(i)]).collect();
+            axes = axes
+                .into_iter()
+                .map(|(start, end)| (n - 1 - end, n - 1 - start))
+                .collect();
+        }
+
+        Self {
+            vertices,
+            normals,
+            axes,
+        }
+    }
+
+    fn as_ref(&self) -> PolygonRef<'_> {
+        PolygonRef {
+            vertices: &self.vertices,
+            normals: &self.normals,
+            axes: &self.axes,
+        }
+    }
+}
+
+/// Normal of the edge from `v1` to `v2` pointing out of a counterclockwise polygon
+fn outward_normal(v1: Vec2, v2: Vec2) -> Vec2 {
+    -(v2 - v1).perp().normalize()
+}
 
-            let axis_depth = (other_max - self_min).min(self_max - other_min);
-            if axis_depth < response_depth {
-                response_depth = axis_depth;
-                response_normal = *normal;
+/// Side of the polygon with the largest separation to the other polygon as (side, separation).
+///
+/// A positive separation means there is a gap between the polygons.
+fn max_separation(polygon: PolygonRef<'_>, other_vertices: &[Vec2]) -> ((usize, usize), f32) {
+    polygon
+        .axes
+        .iter()
+        .map(|&(start, end)| {
+            let (normal, vertex) = (polygon.normals[start], polygon.vertices[start]);
+            let separation = other_vertices
+                .iter()
+                .map(|&vert| normal.dot(vert - vertex))
+                .fold(f32::MAX, f32::min);
+            ((start, end), separation)
+        })
+        .max_by(|(_, s1), (_, s2)| s1.total_cmp(s2))
+        .expect("We cannot have empty polygons")
+}
+
+/// Cuts off the part of the segment where `direction.dot(point) < offset`
+fn clip_segment(p1: Vec2, p2: Vec2, direction: Vec2, offset: f32) -> Option<(Vec2, Vec2)> {
+    let d1 = direction.dot(p1) - offset;
+    let d2 = direction.dot(p2) - offset;
+
+    match (d1 >= 0.0, d2 >= 0.0) {
+        (true, true) => Some((p1, p2)),
+        (false, false) => None,
+        _ => {
+            let intersection = p1 + (p2 - p1) * (d1 / (d1 - d2));
+            if d1 < 0.0 {
+                Some((intersection, p2))
+            } else {
+                Some((p1, intersection))
             }
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::Vector2;
 
-        // Normals where not normalized, so we need to transform the response depth and normal
-        response_depth /= response_normal.length();
-        response_normal = response_normal.normalize();
+    use super::*;
 
-        // Normal is not always pointing in direction 'self to other'
-        let direction = other_transform.translation.truncate() - transform.translation.truncate();
-        if direction.dot(response_normal) < 0.0 {
-            // Pointing in opposite directions
-            response_normal = -response_normal;
+    fn polygon(shape: Shape) -> ConvexPolygon {
+        shape.into_convex_polygon().ok().unwrap()
+    }
+
+    #[test]
+    fn circle_circle() {
+        let response = Circle::new(1.0)
+            .collides(
+                &Circle::new(2.0),
+                &Transform::IDENTITY,
+                &Transform::from_xyz(0.0, 2.0, 0.0),
+            )
+            .unwrap();
+
+        assert_eq!(response.normal, Vec2::Y);
+        assert_eq!(response.depth, 1.0);
+        assert_eq!(
+            response.contacts,
+            [ContactPoint {
+                point: Vec2::new(0.0, 0.5),
+                normal: Vec2::Y,
+                depth: 1.0
+            }]
+        );
+    }
+
+    #[test]
+    fn separated_rects() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let response = rect.collides(
+            &rect,
+            &Transform::IDENTITY,
+            &Transform::from_xyz(2.5, 0.0, 0.0),
+        );
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn rect_on_rect_has_two_contacts() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let response = rect
+            .collides(
+                &rect,
+                &Transform::IDENTITY,
+                &Transform::from_xyz(1.5, 0.5, 0.0),
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::X));
+        assert_abs_diff_eq!(response.depth, 0.5);
+        assert_eq!(response.contacts.len(), 2);
+        let mut points: Vec<Vec2> = response.contacts.iter().map(|c| c.point).collect();
+        points.sort_by(|p1, p2| p1.y.total_cmp(&p2.y));
+        assert_abs_diff_eq!(
+            Vector2::from(points[0]),
+            Vector2::from(Vec2::new(0.5, -0.5))
+        );
+        assert_abs_diff_eq!(Vector2::from(points[1]), Vector2::from(Vec2::new(0.5, 1.0)));
+        for contact in &response.contacts {
+            assert_abs_diff_eq!(contact.depth, 0.5);
         }
+    }
 
-        Some(CollisionResponse {
-            normal: response_normal,
-            depth: response_depth,
-        })
+    #[test]
+    fn mirrored_polygon() {
+        //  ____
+        // |    \
+        // |_____\
+        let trapezoid = ConvexPolygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ]);
+        let transform = Transform::from_xyz(0.0, 0.8, 0.0).with_scale(Vec3::new(-1.0, 2.0, 1.0));
+
+        let response = trapezoid
+            .collides(
+                &polygon(Shape::rect(4.0, 2.0)),
+                &transform,
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.2, epsilon = 1e-6);
+        let mut points: Vec<Vec2> = response.contacts.iter().map(|c| c.point).collect();
+        points.sort_by(|p1, p2| p1.x.total_cmp(&p2.x));
+        assert_abs_diff_eq!(
+            Vector2::from(points[0]),
+            Vector2::from(Vec2::new(-2.0, 1.0))
+        );
+        assert_abs_diff_eq!(Vector2::from(points[1]), Vector2::from(Vec2::new(0.0, 1.0)));
+
+        // The slanted side faces to the upper left after mirroring
+        let normal = Vec2::new(-2.0, 1.0).normalize();
+        let center = Vec2::new(-1.5, 1.8) + 0.5 * normal;
+        let response = Circle::new(1.0)
+            .collides(
+                &trapezoid,
+                &Transform::from_translation(center.extend(0.0)),
+                &transform,
+            )
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-normal));
+        assert_abs_diff_eq!(response.depth, 0.5, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn vertex_in_middle_of_side() {
+        let triangle = ConvexPolygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        ]);
+
+        // Below the second half of the bottom side
+        let response = Circle::new(0.5)
+            .collides(
+                &triangle,
+                &Transform::from_xyz(1.5, -0.3, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.2, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::new(1.5, 0.0)),
+            epsilon = 1e-6
+        );
+
+        let response = triangle
+            .collides(
+                &polygon(Shape::rect(0.6, 1.0)),
+                &Transform::IDENTITY,
+                &Transform::from_xyz(1.5, -0.4, 0.0),
+            )
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-6);
+        let mut points: Vec<f32> = response.contacts.iter().map(|c| c.point.x).collect();
+        points.sort_by(f32::total_cmp);
+        assert_eq!(points.len(), 2);
+        assert_abs_diff_eq!(points[0], 1.2, epsilon = 1e-6);
+        assert_abs_diff_eq!(points[1], 1.8, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn rotated_rect_corner_has_one_contact() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        // Diamond standing with one corner in the top face of the rect
+        let response = rect
+            .collides(
+                &rect,
+                &Transform::IDENTITY,
+                &Transform::from_xyz(0.0, 2.0_f32.sqrt() + 0.8, 0.0)
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.2, epsilon = 1e-5);
+        assert_eq!(response.contacts.len(), 1);
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::new(0.0, 0.8)),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn support_map_fallback() {
+        let rect = Shape::rect(2.0, 2.0);
+        // Diamond standing with one corner in the top face of the rect
+        let transform = Transform::IDENTITY;
+        let other_transform = Transform::from_xyz(0.0, 2.0_f32.sqrt() + 0.8, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+
+        let expected = rect.collides(&rect, &transform, &other_transform).unwrap();
+        let response = collide_support_maps(&rect, &rect, &transform, &other_transform).unwrap();
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(expected.normal),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(response.depth, expected.depth, epsilon = 1e-5);
+
+        // Chains and compounds have no support map
+        let polyline = Shape::polyline(vec![Vec2::new(-2.0, 1.0), Vec2::new(2.0, 1.0)]);
+        assert!(collide_support_maps(&rect, &polyline, &transform, &transform).is_none());
+    }
+
+    #[test]
+    fn scaled_capsule_matches_its_bounds() {
+        let capsule = Shape::capsule(Vec2::new(0.0, -0.5), Vec2::new(0.0, 0.5), 0.5);
+        let transform = Transform::from_scale(Vec3::new(2.0, 2.0, 1.0));
+        let rect = Shape::rect(2.0, 2.0);
+        let rect_transform = Transform::from_xyz(1.8, 0.0, 0.0);
+
+        // The radius grows to 1 like the bounding box
+        assert_abs_diff_eq!(capsule.aabb(&transform).max.x, 1.0, epsilon = 1e-6);
+        let response = capsule
+            .collides(&rect, &transform, &rect_transform)
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::X));
+        assert_abs_diff_eq!(response.depth, 0.2, epsilon = 1e-5);
+
+        let fallback = collide_support_maps(&capsule, &rect, &transform, &rect_transform).unwrap();
+        assert_abs_diff_eq!(fallback.depth, response.depth, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn reference_face_of_other_polygon() {
+        let small = polygon(Shape::rect(1.0, 1.0));
+        let large = polygon(Shape::rect(10.0, 2.0));
+        // Tilted, so only one corner of the small rect reaches into the top face of the large one
+        let angle = 10.0_f32.to_radians();
+        let response = small
+            .collides(
+                &large,
+                &Transform::from_xyz(0.0, 1.5, 0.0).with_rotation(Quat::from_rotation_z(angle)),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        let lowest_corner = 1.5 - 0.5 * (angle.cos() + angle.sin());
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 1.0 - lowest_corner, epsilon = 1e-5);
+        assert_eq!(response.contacts.len(), 1);
+        assert_abs_diff_eq!(response.contacts[0].point.y, lowest_corner, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn circle_on_polygon_face() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let response = Circle::new(1.0)
+            .collides(
+                &rect,
+                &Transform::from_xyz(0.0, 1.5, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.5);
+        assert_eq!(response.contacts.len(), 1);
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::new(0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn circle_on_polygon_corner() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let response = rect
+            .collides(
+                &Circle::new(1.0),
+                &Transform::IDENTITY,
+                &Transform::from_xyz(1.5, 1.5, 0.0),
+            )
+            .unwrap();
+
+        let diagonal = Vec2::ONE.normalize();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(diagonal));
+        assert_abs_diff_eq!(response.depth, 1.0 - 0.5 * 2.0_f32.sqrt());
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::ONE)
+        );
+    }
+
+    #[test]
+    fn circle_outside_polygon_corner() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let response = Circle::new(1.0).collides(
+            &rect,
+            &Transform::from_xyz(1.8, 1.8, 0.0),
+            &Transform::IDENTITY,
+        );
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn circle_on_capsule() {
+        let capsule = Capsule::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+        let response = capsule
+            .collides(
+                &Circle::new(0.5),
+                &Transform::IDENTITY,
+                &Transform::from_xyz(0.5, 0.8, 0.0),
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.2, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::new(0.5, 0.4)),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn capsule_lying_on_rect_has_two_contacts() {
+        let capsule = Capsule::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+        let rect = polygon(Shape::rect(4.0, 2.0));
+        let response = capsule
+            .collides(
+                &rect,
+                &Transform::from_xyz(0.0, 1.4, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-6);
+        let mut points: Vec<Vec2> = response.contacts.iter().map(|c| c.point).collect();
+        points.sort_by(|p1, p2| p1.x.total_cmp(&p2.x));
+        assert_abs_diff_eq!(
+            Vector2::from(points[0]),
+            Vector2::from(Vec2::new(-1.0, 0.9)),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(points[1]),
+            Vector2::from(Vec2::new(1.0, 0.9)),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn capsule_end_on_rect_corner() {
+        let capsule = Capsule::new(Vec2::ZERO, Vec2::ONE, 0.5);
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let response = rect
+            .collides(
+                &capsule,
+                &Transform::IDENTITY,
+                &Transform::from_xyz(1.2, 1.2, 0.0),
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(Vec2::ONE.normalize()),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(response.depth, 0.5 - 0.2 * 2.0_f32.sqrt(), epsilon = 1e-6);
+        assert_eq!(response.contacts.len(), 1);
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::ONE),
+            epsilon = 1e-6
+        );
+
+        // Round end keeps the capsule away from the corner
+        let response = rect.collides(
+            &capsule,
+            &Transform::IDENTITY,
+            &Transform::from_xyz(1.4, 1.4, 0.0),
+        );
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn parallel_capsules_have_two_contacts() {
+        let capsule = Capsule::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+        let response = capsule
+            .collides(
+                &capsule,
+                &Transform::IDENTITY,
+                &Transform::from_xyz(1.0, 0.8, 0.0),
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.2, epsilon = 1e-6);
+        let mut points: Vec<f32> = response.contacts.iter().map(|c| c.point.x).collect();
+        points.sort_by(f32::total_cmp);
+        assert_abs_diff_eq!(points[0], 0.0);
+        assert_abs_diff_eq!(points[1], 1.0);
+    }
+
+    #[test]
+    fn crossing_capsules() {
+        let horizontal = Capsule::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+        let vertical = Capsule::new(Vec2::new(0.0, -1.0), Vec2::new(0.0, 1.0), 0.5);
+        let response = horizontal
+            .collides(
+                &vertical,
+                &Transform::IDENTITY,
+                &Transform::from_xyz(0.0, 0.5, 0.0),
+            )
+            .unwrap();
+
+        // Pushing the vertical capsule up by the depth separates the capsules
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 1.5);
+        assert_eq!(response.contacts.len(), 1);
+    }
+
+    #[test]
+    fn circle_on_segment() {
+        let segment = Segment::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0));
+        let response = Circle::new(0.5)
+            .collides(
+                &segment,
+                &Transform::from_xyz(0.0, -0.4, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn rect_slides_over_polyline_seam() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        // Bottom right corner of the sunken rect reaches just over the seam
+        let rect_transform = Transform::from_xyz(-0.95, 0.9, 0.0);
+
+        // A single segment catches the corner and pushes the rect back
+        let segment = Segment::new(Vec2::new(0.0, 0.0), Vec2::new(4.0, 0.0));
+        let response = segment
+            .collides(&rect, &Transform::IDENTITY, &rect_transform)
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::X));
+
+        let polyline = Polyline::new(vec![
+            Vec2::new(-4.0, 0.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+        ]);
+        let response = polyline
+            .collides(&rect, &Transform::IDENTITY, &rect_transform)
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-6);
+        let mut points: Vec<f32> = response.contacts.iter().map(|c| c.point.x).collect();
+        points.sort_by(f32::total_cmp);
+        assert_abs_diff_eq!(points[0], -1.95, epsilon = 1e-6);
+        assert_abs_diff_eq!(points[1], 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn one_sided_polyline() {
+        // From right to left, so the top is the right side
+        let vertices = vec![Vec2::new(4.0, 0.0), Vec2::new(-4.0, 0.0)];
+        let one_sided = Polyline::one_sided(vertices.clone());
+        let two_sided = Polyline::new(vertices);
+        let circle = Circle::new(0.5);
+        let above = Transform::from_xyz(0.0, 0.3, 0.0);
+        let below = Transform::from_xyz(0.0, -0.3, 0.0);
+
+        let response = one_sided
+            .collides(&circle, &Transform::IDENTITY, &above)
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert!(one_sided
+            .collides(&circle, &Transform::IDENTITY, &below)
+            .is_none());
+
+        let response = two_sided
+            .collides(&circle, &Transform::IDENTITY, &below)
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+    }
+
+    #[test]
+    fn circle_on_polyline_corner() {
+        // Edge of a cliff
+        let polyline = Polyline::new(vec![
+            Vec2::new(-2.0, 0.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, -2.0),
+        ]);
+        let response = Circle::new(0.5)
+            .collides(
+                &polyline,
+                &Transform::from_xyz(0.3, 0.3, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(-Vec2::ONE.normalize()),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(response.depth, 0.5 - 0.3 * 2.0_f32.sqrt(), epsilon = 1e-6);
+        assert_eq!(response.contacts.len(), 1);
+    }
+
+    #[test]
+    fn circle_on_heightfield_slope() {
+        // Long slope along y = x after moving it up by 0.5
+        let heights: Vec<f32> = (0..10000).map(|i| i as f32 - 5000.0).collect();
+        let heightfield = Heightfield::new(heights, 1.0);
+        let center = Vec2::new(0.2, 0.2) + Vec2::new(-1.0, 1.0).normalize() * 0.4;
+        let response = heightfield
+            .collides(
+                &Circle::new(0.5),
+                &Transform::from_xyz(0.0, 0.5, 0.0),
+                &Transform::from_translation(center.extend(0.0)),
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(Vec2::new(-1.0, 1.0).normalize()),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn capsule_lying_on_heightfield() {
+        let heightfield = Heightfield::new(vec![0.0; 101], 0.5);
+        let capsule = Capsule::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+        let response = capsule
+            .collides(
+                &heightfield,
+                &Transform::from_xyz(0.25, 0.4, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-6);
+        let mut points: Vec<f32> = response.contacts.iter().map(|c| c.point.x).collect();
+        points.sort_by(f32::total_cmp);
+        assert_eq!(points.len(), 2);
+        assert_abs_diff_eq!(points[0], -0.75, epsilon = 1e-6);
+        assert_abs_diff_eq!(points[1], 1.25, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn shapes_outside_of_heightfield() {
+        let heightfield = Heightfield::new(vec![0.0; 11], 1.0);
+        let circle = Circle::new(0.5);
+
+        // Beside and below the surface
+        let beside = Transform::from_xyz(5.6, 0.0, 0.0);
+        assert!(heightfield
+            .collides(&circle, &Transform::IDENTITY, &beside)
+            .is_none());
+        let below = Transform::from_xyz(0.0, -0.3, 0.0);
+        assert!(heightfield
+            .collides(&circle, &Transform::IDENTITY, &below)
+            .is_none());
+    }
+
+    #[test]
+    fn table_stands_on_both_legs() {
+        let table = Shape::compound(vec![
+            (
+                Transform2d::from_translation(Vec2::new(0.0, 1.5)),
+                Shape::rect(6.0, 1.0),
+            ),
+            (
+                Transform2d::from_translation(Vec2::new(-2.5, 0.0)),
+                Shape::rect(1.0, 2.0),
+            ),
+            (
+                Transform2d::from_translation(Vec2::new(2.5, 0.0)),
+                Shape::rect(1.0, 2.0),
+            ),
+        ]);
+        let ground = Shape::rect(10.0, 2.0);
+        let response = table
+            .collides(
+                &ground,
+                &Transform::from_xyz(0.0, 1.9, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(-Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-6);
+        let mut points: Vec<f32> = response.contacts.iter().map(|c| c.point.x).collect();
+        points.sort_by(f32::total_cmp);
+        assert_eq!(points.len(), 2);
+        assert_abs_diff_eq!(points[0], -3.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(points[1], 3.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn box_in_corner_touches_floor_and_wall() {
+        let platform = Shape::compound(vec![
+            (Transform2d::default(), Shape::rect(6.0, 1.0)),
+            (
+                Transform2d::from_translation(Vec2::new(-2.5, 2.5)),
+                Shape::rect(1.0, 4.0),
+            ),
+        ]);
+        let response = platform
+            .collides(
+                &Shape::rect(2.0, 2.0),
+                &Transform::IDENTITY,
+                &Transform::from_xyz(-1.05, 1.4, 0.0),
+            )
+            .unwrap();
+
+        // The floor is hit deepest
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::Y));
+        assert_abs_diff_eq!(response.depth, 0.1, epsilon = 1e-5);
+
+        let (floor, wall): (Vec<ContactPoint>, Vec<ContactPoint>) = response
+            .contacts
+            .iter()
+            .partition(|contact| contact.normal.dot(Vec2::Y) > 0.5);
+        assert_eq!(floor.len(), 2);
+        assert_eq!(wall.len(), 2);
+        for contact in floor {
+            assert_abs_diff_eq!(Vector2::from(contact.normal), Vector2::from(Vec2::Y));
+            assert_abs_diff_eq!(contact.depth, 0.1, epsilon = 1e-5);
+        }
+        for contact in wall {
+            assert_abs_diff_eq!(Vector2::from(contact.normal), Vector2::from(Vec2::X));
+            assert_abs_diff_eq!(contact.depth, 0.05, epsilon = 1e-5);
+            assert_abs_diff_eq!(contact.point.x, -2.05, epsilon = 1e-5);
+            assert!((0.5 - 1e-5..=2.4 + 1e-5).contains(&contact.point.y));
+        }
+    }
+
+    #[test]
+    fn compound_with_compound() {
+        let compound = Shape::compound(vec![(
+            Transform2d::new(Vec2::new(1.0, 0.0), std::f32::consts::FRAC_PI_2),
+            Shape::circle(1.0),
+        )]);
+        let response = Shape::circle(1.0)
+            .collides(
+                &compound,
+                &Transform::IDENTITY,
+                &Transform::from_xyz(0.5, 0.0, 0.0),
+            )
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::X));
+        assert_abs_diff_eq!(response.depth, 0.5);
+
+        let response = compound
+            .collides(
+                &compound,
+                &Transform::from_xyz(-1.5, 0.0, 0.0),
+                &Transform::IDENTITY,
+            )
+            .unwrap();
+        assert_abs_diff_eq!(Vector2::from(response.normal), Vector2::from(Vec2::X));
+        assert_abs_diff_eq!(response.depth, 0.5);
+
+        let response = compound.collides(
+            &compound,
+            &Transform::from_xyz(-3.0, 0.0, 0.0),
+            &Transform::IDENTITY,
+        );
+        assert!(response.is_none());
     }
 }
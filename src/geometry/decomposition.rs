@@ -0,0 +1,249 @@
+use bevy::prelude::Vec2;
+use itertools::Itertools;
+
+use crate::math::closest_points_between_segments;
+
+/// Splits a simple polygon into convex pieces.
+///
+/// The polygon is triangulated by ear clipping, afterwards neighboring pieces are merged as long
+/// as they stay convex (Hertel-Mehlhorn). This produces at most four times the minimal number of
+/// pieces. The pieces are in counterclockwise order, independent of the order of the input.
+pub fn decompose_polygon(vertices: &[Vec2]) -> Vec<Vec<Vec2>> {
+    let mut vertices = remove_collinear(vertices);
+    let signed_area: f32 = vertices
+        .iter()
+        .circular_tuple_windows()
+        .map(|(p1, p2)| p1.perp_dot(*p2))
+        .sum();
+    if signed_area < 0.0 {
+        vertices.reverse();
+    }
+
+    let mut pieces = triangulate(&vertices);
+    merge_convex_pieces(&vertices, &mut pieces);
+    pieces
+        .into_iter()
+        .map(|piece| remove_collinear(&piece.iter().map(|&i| vertices[i]).collect_vec()))
+        .collect()
+}
+
+/// Whether two edges of the polygon, which do not follow each other, touch or cross
+pub fn is_self_intersecting(vertices: &[Vec2]) -> bool {
+    let n = vertices.len();
+    let size = vertices
+        .iter()
+        .circular_tuple_windows()
+        .map(|(p1, p2)| p1.distance_squared(*p2))
+        .fold(0.0, f32::max);
+    (0..n).tuple_combinations().any(|(i, j)| {
+        // Neighboring edges always share a vertex
+        if j == i + 1 || (i == 0 && j == n - 1) {
+            return false;
+        }
+        let (p, q) = closest_points_between_segments(
+            vertices[i],
+            vertices[(i + 1) % n],
+            vertices[j],
+            vertices[(j + 1) % n],
+        );
+        p.distance_squared(q) <= 1e-12 * size
+    })
+}
+
+/// Triangles of a counterclockwise polygon as indices of its vertices
+fn triangulate(vertices: &[Vec2]) -> Vec<Vec<usize>> {
+    let mut remaining: Vec<usize> = (0..vertices.len()).collect();
+    let mut triangles = Vec::new();
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (prev, curr, next) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            let (a, b, c) = (vertices[prev], vertices[curr], vertices[next]);
+            is_convex(a, b, c)
+                && remaining
+                    .iter()
+                    .filter(|&&j| j != prev && j != curr && j != next)
+                    .all(|&j| !in_triangle(vertices[j], a, b, c))
+        });
+        // Only self-intersecting polygons have no ear, cut off any corner to make progress
+        let i = ear.unwrap_or(0);
+
+        triangles.push(vec![
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+    if remaining.len() == 3 {
+        triangles.push(remaining);
+    }
+    triangles
+}
+
+/// Merges pieces sharing an edge as long as the merged piece is convex
+fn merge_convex_pieces(vertices: &[Vec2], pieces: &mut Vec<Vec<usize>>) {
+    let mut merged_any = true;
+    while merged_any {
+        merged_any = false;
+        'search: for i in 0..pieces.len() {
+            for j in i + 1..pieces.len() {
+                if let Some(merged) = merge_pieces(&pieces[i], &pieces[j]) {
+                    let is_convex = merged
+                        .iter()
+                        .circular_tuple_windows()
+                        .all(|(&a, &b, &c)| is_convex(vertices[a], vertices[b], vertices[c]));
+                    if is_convex {
+                        pieces[i] = merged;
+                        pieces.swap_remove(j);
+                        merged_any = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Joins two counterclockwise pieces along their shared edge, if they have one
+fn merge_pieces(piece: &[usize], other: &[usize]) -> Option<Vec<usize>> {
+    let (n, m) = (piece.len(), other.len());
+    // Edge from u to v in the piece, which runs from v to u in the other piece
+    let (start, other_start) = (0..n).find_map(|i| {
+        let (u, v) = (piece[i], piece[(i + 1) % n]);
+        (0..m)
+            .find(|&k| other[k] == v && other[(k + 1) % m] == u)
+            .map(|k| ((i + 1) % n, (k + 1) % m))
+    })?;
+
+    // Around the piece from v to u, then around the other piece between u and v
+    let mut merged: Vec<usize> = (0..n).map(|i| piece[(start + i) % n]).collect();
+    merged.extend((1..m - 1).map(|k| other[(other_start + k) % m]));
+    Some(merged)
+}
+
+/// Whether the corner at `b` turns left or goes straight on
+fn is_convex(a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(c - b) >= 0.0
+}
+
+/// Whether the point lies in the counterclockwise triangle or on its border
+fn in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(point - a) >= 0.0
+        && (c - b).perp_dot(point - b) >= 0.0
+        && (a - c).perp_dot(point - c) >= 0.0
+}
+
+/// Vertices without duplicates and points in the middle of straight edges
+fn remove_collinear(vertices: &[Vec2]) -> Vec<Vec2> {
+    let mut result: Vec<Vec2> = vertices.to_vec();
+    let mut i = 0;
+    while result.len() > 3 && i < result.len() {
+        let n = result.len();
+        let (a, b, c) = (result[(i + n - 1) % n], result[i], result[(i + 1) % n]);
+        if (b - a).perp_dot(c - b).abs() <= f32::EPSILON * (b - a).length() * (c - b).length() {
+            result.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::polygon_area;
+
+    use super::*;
+
+    fn assert_convex_pieces(vertices: &[Vec2], pieces: &[Vec<Vec2>]) {
+        for piece in pieces {
+            assert!(piece
+                .iter()
+                .circular_tuple_windows()
+                .all(|(&a, &b, &c)| (b - a).perp_dot(c - b) > 0.0));
+        }
+        let area: f32 = pieces.iter().map(|piece| polygon_area(piece)).sum();
+        assert_abs_diff_eq!(area, polygon_area(vertices), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn self_intersections() {
+        let square = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
+        assert!(!is_self_intersecting(&square));
+
+        let bow_tie = [Vec2::ZERO, Vec2::ONE, Vec2::X, Vec2::Y];
+        assert!(is_self_intersecting(&bow_tie));
+
+        // Tip of the notch touches the opposite edge
+        let touching = [
+            Vec2::ZERO,
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert!(is_self_intersecting(&touching));
+    }
+
+    #[test]
+    fn convex_polygon_stays_one_piece() {
+        let vertices = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        let pieces = decompose_polygon(&vertices);
+
+        assert_eq!(pieces.len(), 1);
+        // Point in the middle of the bottom edge is dropped
+        assert_eq!(pieces[0].len(), 4);
+        assert_convex_pieces(&vertices, &pieces);
+    }
+
+    #[test]
+    fn l_shape() {
+        //  ___
+        // |   |
+        // |   |______
+        // |__________|
+        let vertices = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, 0.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ];
+        let pieces = decompose_polygon(&vertices);
+
+        assert_eq!(pieces.len(), 2);
+        assert_convex_pieces(&vertices, &pieces);
+    }
+
+    #[test]
+    fn clockwise_star() {
+        let vertices: Vec<Vec2> = (0..10)
+            .map(|i| {
+                let phi = -(i as f32) * std::f32::consts::PI / 5.0;
+                let radius = if i % 2 == 0 { 2.0 } else { 1.0 };
+                radius * Vec2::new(phi.cos(), phi.sin())
+            })
+            .collect();
+        let pieces = decompose_polygon(&vertices);
+
+        // Fewer pieces than the eight triangles of the triangulation
+        assert!(pieces.len() < 8);
+        assert_convex_pieces(&vertices, &pieces);
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::{
+    prelude::*,
+    reflect::{impl_from_reflect_value, impl_reflect_value},
+};
+
+use super::broad_phase::BroadPhase;
+
+bitflags::bitflags! {
+    /// Groups a collider can be a member of or interact with
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Group: u32 {
+        const GROUP_1 = 1 << 0;
+        const GROUP_2 = 1 << 1;
+        const GROUP_3 = 1 << 2;
+        const GROUP_4 = 1 << 3;
+        const GROUP_5 = 1 << 4;
+        const GROUP_6 = 1 << 5;
+        const GROUP_7 = 1 << 6;
+        const GROUP_8 = 1 << 7;
+        const GROUP_9 = 1 << 8;
+        const GROUP_10 = 1 << 9;
+        const GROUP_11 = 1 << 10;
+        const GROUP_12 = 1 << 11;
+        const GROUP_13 = 1 << 12;
+        const GROUP_14 = 1 << 13;
+        const GROUP_15 = 1 << 14;
+        const GROUP_16 = 1 << 15;
+        const GROUP_17 = 1 << 16;
+        const GROUP_18 = 1 << 17;
+        const GROUP_19 = 1 << 18;
+        const GROUP_20 = 1 << 19;
+        const GROUP_21 = 1 << 20;
+        const GROUP_22 = 1 << 21;
+        const GROUP_23 = 1 << 22;
+        const GROUP_24 = 1 << 23;
+        const GROUP_25 = 1 << 24;
+        const GROUP_26 = 1 << 25;
+        const GROUP_27 = 1 << 26;
+        const GROUP_28 = 1 << 27;
+        const GROUP_29 = 1 << 28;
+        const GROUP_30 = 1 << 29;
+        const GROUP_31 = 1 << 30;
+        const GROUP_32 = 1 << 31;
+        const ALL = u32::MAX;
+        const NONE = 0;
+    }
+}
+
+// Flags are reflected as a whole, their inner representation is private to `bitflags`
+impl_reflect_value!(Group(Debug, PartialEq, Hash));
+impl_from_reflect_value!(Group);
+
+/// Restricts which colliders can collide with each other.
+///
+/// Two colliders only collide if each is a member of a group the other one filters for.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct CollisionGroups {
+    /// Groups the collider is part of
+    pub memberships: Group,
+    /// Groups the collider can collide with
+    pub filters: Group,
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self {
+            memberships: Group::ALL,
+            filters: Group::ALL,
+        }
+    }
+}
+
+impl CollisionGroups {
+    pub fn new(memberships: Group, filters: Group) -> Self {
+        Self {
+            memberships,
+            filters,
+        }
+    }
+
+    /// Whether colliders with these groups can collide
+    pub fn interacts_with(&self, other: &CollisionGroups) -> bool {
+        self.memberships.intersects(other.filters) && other.memberships.intersects(self.filters)
+    }
+}
+
+/// Selects the colliders a spatial query can find
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryFilter {
+    /// Only colliders interacting with these groups are found
+    pub groups: CollisionGroups,
+    /// Collider which is never found, e.g. the one of the shooter of a ray
+    pub excluded: Option<Entity>,
+}
+
+impl QueryFilter {
+    pub fn new(groups: CollisionGroups) -> Self {
+        Self {
+            groups,
+            excluded: None,
+        }
+    }
+
+    pub fn with_excluded(mut self, entity: Entity) -> Self {
+        self.excluded = Some(entity);
+        self
+    }
+
+    /// Whether the collider of the entity with the given groups can be found
+    pub fn accepts(&self, entity: Entity, groups: &CollisionGroups) -> bool {
+        self.excluded != Some(entity) && self.groups.interacts_with(groups)
+    }
+}
+
+/// User defined check, which is run for every pair of colliders with overlapping bounds.
+///
+/// Pairs for which it returns `false` are skipped before the exact collision test. This can be
+/// used e.g. to ignore collisions of a bullet with its shooter.
+#[derive(Resource)]
+pub struct CollisionFilterHook(Box<PairFilter>);
+
+type PairFilter = dyn Fn(&World, Entity, Entity) -> bool + Send + Sync;
+
+impl CollisionFilterHook {
+    pub fn new(filter: impl Fn(&World, Entity, Entity) -> bool + Send + Sync + 'static) -> Self {
+        Self(Box::new(filter))
+    }
+}
+
+/// Removes the pairs from the broad phase rejected by the [`CollisionFilterHook`]
+pub fn filter_pairs(world: &mut World) {
+    if !world.contains_resource::<CollisionFilterHook>() {
+        return;
+    }
+    world.resource_scope(|world, mut broad_phase: Mut<BroadPhase>| {
+        let hook = world.resource::<CollisionFilterHook>();
+        broad_phase.retain_pairs(|entity1, entity2| (hook.0)(world, entity1, entity2));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_groups_interact() {
+        assert!(CollisionGroups::default().interacts_with(&CollisionGroups::default()));
+    }
+
+    #[test]
+    fn groups_have_to_match_both_ways() {
+        let terrain = CollisionGroups::new(Group::GROUP_1, Group::ALL);
+        let debris = CollisionGroups::new(Group::GROUP_2, Group::GROUP_1);
+        let enemy = CollisionGroups::new(Group::GROUP_3, Group::ALL ^ Group::GROUP_3);
+
+        assert!(debris.interacts_with(&terrain));
+        assert!(terrain.interacts_with(&debris));
+        assert!(!debris.interacts_with(&enemy));
+        assert!(!enemy.interacts_with(&enemy));
+        assert!(enemy.interacts_with(&terrain));
+    }
+
+    #[test]
+    fn groups_are_reflected() {
+        let groups = CollisionGroups::new(Group::GROUP_2, Group::GROUP_1 | Group::GROUP_3);
+        let mut reflected = CollisionGroups::default();
+        reflected.apply(&groups);
+        assert_eq!(reflected, groups);
+        assert_eq!(
+            CollisionGroups::from_reflect(groups.as_reflect()),
+            Some(groups)
+        );
+    }
+}
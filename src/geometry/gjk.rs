@@ -0,0 +1,523 @@
+use bevy::prelude::*;
+
+use super::{
+    collision::{CollisionResponse, ContactPoint},
+    shape::{capsule::Capsule, circle::Circle, convex_polygon::ConvexPolygon, segment::Segment},
+};
+
+/// Maximal number of iterations of GJK and EPA before the current result is accepted
+const MAX_ITERATIONS: usize = 32;
+
+/// Relative tolerance at which GJK and EPA count as converged
+const TOLERANCE: f32 = 1e-5;
+
+/// Convex shape described by its farthest point in every direction
+pub trait SupportMap {
+    /// Point of the shape in local coordinates which is the farthest in the direction
+    fn local_support_point(&self, direction: Vec2) -> Vec2;
+
+    /// Point of the shape in world coordinates which is the farthest in the direction
+    fn support_point(&self, direction: Vec2, transform: &Transform) -> Vec2 {
+        // The farthest point of a transformed shape is the transformed farthest point in the
+        // direction transformed by the transposed rotation and scaling
+        let local_direction = transform.scale.truncate()
+            * (transform.rotation.inverse() * direction.extend(0.0)).truncate();
+        transform
+            .transform_point(self.local_support_point(local_direction).extend(0.0))
+            .truncate()
+    }
+}
+
+impl SupportMap for Circle {
+    fn local_support_point(&self, direction: Vec2) -> Vec2 {
+        direction.normalize_or_zero() * self.radius()
+    }
+
+    fn support_point(&self, direction: Vec2, transform: &Transform) -> Vec2 {
+        // Stays round under non-uniform scaling, like in the narrow phase
+        transform.translation.truncate()
+            + direction.normalize_or_zero() * self.world_radius(transform)
+    }
+}
+
+impl SupportMap for ConvexPolygon {
+    fn local_support_point(&self, direction: Vec2) -> Vec2 {
+        self.vertices()
+            .iter()
+            .copied()
+            .max_by(|v1, v2| v1.dot(direction).total_cmp(&v2.dot(direction)))
+            .expect("We cannot have empty polygons")
+    }
+}
+
+impl SupportMap for Capsule {
+    fn local_support_point(&self, direction: Vec2) -> Vec2 {
+        let end = if self.a().dot(direction) > self.b().dot(direction) {
+            self.a()
+        } else {
+            self.b()
+        };
+        end + direction.normalize_or_zero() * self.radius()
+    }
+
+    fn support_point(&self, direction: Vec2, transform: &Transform) -> Vec2 {
+        // Stays round under non-uniform scaling, like in the narrow phase
+        let [a, b] =
+            [self.a(), self.b()].map(|v| transform.transform_point(v.extend(0.0)).truncate());
+        let end = if a.dot(direction) > b.dot(direction) {
+            a
+        } else {
+            b
+        };
+        end + direction.normalize_or_zero() * self.world_radius(transform)
+    }
+}
+
+impl SupportMap for Segment {
+    fn local_support_point(&self, direction: Vec2) -> Vec2 {
+        if self.a().dot(direction) > self.b().dot(direction) {
+            self.a()
+        } else {
+            self.b()
+        }
+    }
+}
+
+/// Closest points of two separated shapes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoints {
+    /// Point on the first shape in world coordinates
+    pub point: Vec2,
+    /// Point on the second shape in world coordinates
+    pub other_point: Vec2,
+    /// Distance between the points
+    pub distance: f32,
+}
+
+/// Point of the Minkowski difference of two shapes, together with the points it is made of
+#[derive(Clone, Copy, Debug)]
+struct SupportPoint {
+    /// `point - other_point`
+    difference: Vec2,
+    point: Vec2,
+    other_point: Vec2,
+}
+
+impl SupportPoint {
+    fn new(
+        shape: &(impl SupportMap + ?Sized),
+        transform: &Transform,
+        other: &(impl SupportMap + ?Sized),
+        other_transform: &Transform,
+        direction: Vec2,
+    ) -> Self {
+        let point = shape.support_point(direction, transform);
+        let other_point = other.support_point(-direction, other_transform);
+        Self {
+            difference: point - other_point,
+            point,
+            other_point,
+        }
+    }
+
+    /// Point on the segment to `other` with the interpolation factor `t`
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            difference: self.difference.lerp(other.difference, t),
+            point: self.point.lerp(other.point, t),
+            other_point: self.other_point.lerp(other.other_point, t),
+        }
+    }
+}
+
+/// Result of the GJK algorithm
+enum Gjk {
+    /// The origin is enclosed by the counterclockwise triangle of the Minkowski difference
+    Intersecting([SupportPoint; 3]),
+    /// The shapes touch, so the origin lies on the border of the Minkowski difference
+    Touching,
+    /// Closest point of the Minkowski difference to the origin
+    Separated(SupportPoint),
+}
+
+/// Gilbert-Johnson-Keerthi algorithm searching the point of the Minkowski difference `shape -
+/// other` closest to the origin
+fn gjk(
+    shape: &(impl SupportMap + ?Sized),
+    transform: &Transform,
+    other: &(impl SupportMap + ?Sized),
+    other_transform: &Transform,
+) -> Gjk {
+    let support =
+        |direction: Vec2| SupportPoint::new(shape, transform, other, other_transform, direction);
+
+    let initial_direction =
+        other_transform.translation.truncate() - transform.translation.truncate();
+    let mut simplex = vec![support(if initial_direction == Vec2::ZERO {
+        Vec2::X
+    } else {
+        initial_direction
+    })];
+    let mut closest = simplex[0];
+
+    for _ in 0..MAX_ITERATIONS {
+        match closest_on_simplex(&mut simplex) {
+            Some(point) => closest = point,
+            None => {
+                let [a, b, c] = [simplex[0], simplex[1], simplex[2]];
+                return Gjk::Intersecting(
+                    if (b.difference - a.difference).perp_dot(c.difference - a.difference) > 0.0 {
+                        [a, b, c]
+                    } else {
+                        [a, c, b]
+                    },
+                );
+            }
+        }
+
+        let distance_squared = closest.difference.length_squared();
+        let scale = simplex
+            .iter()
+            .map(|p| p.difference.length_squared())
+            .fold(0.0, f32::max);
+        if distance_squared <= TOLERANCE * TOLERANCE * scale {
+            // The origin lies on a corner or an edge of the simplex. It is only inside of the
+            // Minkowski difference if that extends to both sides of the edge.
+            let [a, b] = match simplex[..] {
+                [a, b] => [a, b],
+                _ => return Gjk::Touching,
+            };
+            let normal = (b.difference - a.difference).perp();
+            let extent = |point: SupportPoint| normal.dot(point.difference - a.difference).abs();
+            let (left, right) = (support(normal), support(-normal));
+            let tolerance = TOLERANCE * normal.length() * scale.sqrt();
+            if extent(left) <= tolerance || extent(right) <= tolerance {
+                return Gjk::Touching;
+            }
+            simplex.push(left);
+            continue;
+        }
+
+        // No point of the Minkowski difference is closer to the origin in the search direction
+        let direction = -closest.difference;
+        let new_point = support(direction);
+        if direction.dot(new_point.difference - closest.difference) <= TOLERANCE * distance_squared
+        {
+            break;
+        }
+        simplex.push(new_point);
+    }
+    Gjk::Separated(closest)
+}
+
+/// Point of the simplex closest to the origin. The simplex is reduced to the points needed to
+/// describe it, or `None` if the simplex is a triangle enclosing the origin.
+fn closest_on_simplex(simplex: &mut Vec<SupportPoint>) -> Option<SupportPoint> {
+    let (closest, reduced) = match simplex[..] {
+        [a] => (a, vec![a]),
+        [a, b] => closest_on_edge(a, b),
+        [a, b, c] => {
+            let [p1, p2, p3] = [a, b, c].map(|p| p.difference);
+            let orientation = (p2 - p1).perp_dot(p3 - p1);
+            // A flat triangle cannot enclose the origin, the closest point is on one of its edges
+            let inside = orientation != 0.0
+                && [(p1, p2), (p2, p3), (p3, p1)]
+                    .into_iter()
+                    .all(|(v1, v2)| orientation * (v2 - v1).perp_dot(-v1) >= 0.0);
+            if inside {
+                return None;
+            }
+
+            [(a, b), (b, c), (c, a)]
+                .into_iter()
+                .map(|(v1, v2)| closest_on_edge(v1, v2))
+                .min_by(|(p1, _), (p2, _)| {
+                    p1.difference
+                        .length_squared()
+                        .total_cmp(&p2.difference.length_squared())
+                })
+                .expect("A triangle has edges")
+        }
+        _ => unreachable!("The simplex has one to three points"),
+    };
+    *simplex = reduced;
+    Some(closest)
+}
+
+/// Point on the edge from `a` to `b` closest to the origin, together with the end points needed
+/// to describe it
+fn closest_on_edge(a: SupportPoint, b: SupportPoint) -> (SupportPoint, Vec<SupportPoint>) {
+    let edge = b.difference - a.difference;
+    let t = if edge == Vec2::ZERO {
+        0.0
+    } else {
+        -a.difference.dot(edge) / edge.length_squared()
+    };
+
+    if t <= 0.0 {
+        (a, vec![a])
+    } else if t >= 1.0 {
+        (b, vec![b])
+    } else {
+        (a.lerp(b, t), vec![a, b])
+    }
+}
+
+/// Expanding Polytope Algorithm, searching the edge of the Minkowski difference closest to the
+/// origin which is enclosed by the triangle. Returns the point on the edge closest to the origin
+/// and the outward normal of the edge.
+fn epa(
+    shape: &(impl SupportMap + ?Sized),
+    transform: &Transform,
+    other: &(impl SupportMap + ?Sized),
+    other_transform: &Transform,
+    triangle: [SupportPoint; 3],
+) -> (SupportPoint, Vec2) {
+    let mut polytope = triangle.to_vec();
+
+    let mut closest = (polytope[0], Vec2::ZERO);
+    for _ in 0..MAX_ITERATIONS {
+        let (edge, normal, distance) = (0..polytope.len())
+            .filter_map(|i| {
+                let (a, b) = (polytope[i], polytope[(i + 1) % polytope.len()]);
+                let normal = -(b.difference - a.difference).perp().try_normalize()?;
+                Some((i, normal, normal.dot(a.difference)))
+            })
+            .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))
+            .expect("The polytope has edges");
+
+        let (a, b) = (polytope[edge], polytope[(edge + 1) % polytope.len()]);
+        let difference = b.difference - a.difference;
+        let t = (-a.difference.dot(difference) / difference.length_squared()).clamp(0.0, 1.0);
+        closest = (a.lerp(b, t), normal);
+
+        let new_point = SupportPoint::new(shape, transform, other, other_transform, normal);
+        if normal.dot(new_point.difference) - distance <= TOLERANCE * distance.max(1.0) {
+            break;
+        }
+        polytope.insert(edge + 1, new_point);
+    }
+    closest
+}
+
+/// Whether the two convex shapes overlap
+pub fn intersects(
+    shape: &(impl SupportMap + ?Sized),
+    transform: &Transform,
+    other: &(impl SupportMap + ?Sized),
+    other_transform: &Transform,
+) -> bool {
+    matches!(
+        gjk(shape, transform, other, other_transform),
+        Gjk::Intersecting(_)
+    )
+}
+
+/// Closest points of two convex shapes, or `None` if they touch or overlap
+pub fn closest_points(
+    shape: &(impl SupportMap + ?Sized),
+    transform: &Transform,
+    other: &(impl SupportMap + ?Sized),
+    other_transform: &Transform,
+) -> Option<ClosestPoints> {
+    match gjk(shape, transform, other, other_transform) {
+        Gjk::Separated(closest) => Some(ClosestPoints {
+            point: closest.point,
+            other_point: closest.other_point,
+            distance: closest.difference.length(),
+        }),
+        _ => None,
+    }
+}
+
+/// Collision of two convex shapes with a single contact point, which works for every pair of
+/// shapes with a [`SupportMap`].
+///
+/// The shapes are tested with GJK, the penetration of overlapping shapes is found by EPA.
+pub fn collide_convex(
+    shape: &(impl SupportMap + ?Sized),
+    transform: &Transform,
+    other: &(impl SupportMap + ?Sized),
+    other_transform: &Transform,
+) -> Option<CollisionResponse> {
+    let Gjk::Intersecting(triangle) = gjk(shape, transform, other, other_transform) else {
+        return None;
+    };
+    let (closest, normal) = epa(shape, transform, other, other_transform, triangle);
+
+    // The Minkowski difference reaches past the origin by the depth
+    let depth = normal.dot(closest.difference);
+    if depth <= 0.0 {
+        return None;
+    }
+    Some(CollisionResponse {
+        normal,
+        depth,
+        contacts: vec![ContactPoint {
+            point: (closest.point + closest.other_point) / 2.0,
+            normal,
+            depth,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        geometry::{collision::CollisionWith, shape::Shape},
+        math::Vector2,
+    };
+
+    use super::*;
+
+    fn polygon(shape: Shape) -> ConvexPolygon {
+        shape.into_convex_polygon().ok().unwrap()
+    }
+
+    #[test]
+    fn support_point_of_transformed_rect() {
+        let rect = polygon(Shape::rect(2.0, 1.0));
+        let transform = Transform::from_xyz(1.0, 0.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2))
+            .with_scale(Vec3::new(2.0, 1.0, 1.0));
+
+        // Rotated by 90°, the long side points up
+        let point = rect.support_point(Vec2::new(1.0, 2.0), &transform);
+        assert_abs_diff_eq!(Vector2::from(point), Vector2::from(Vec2::new(1.5, 2.0)));
+    }
+
+    #[test]
+    fn closest_points_of_circles() {
+        let circle = Circle::new(1.0);
+        let points = closest_points(
+            &circle,
+            &Transform::IDENTITY,
+            &circle,
+            &Transform::from_xyz(3.0, 0.0, 0.0),
+        )
+        .unwrap();
+
+        assert_abs_diff_eq!(points.distance, 1.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(
+            Vector2::from(points.point),
+            Vector2::from(Vec2::X),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(points.other_point),
+            Vector2::from(Vec2::new(2.0, 0.0)),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn closest_points_of_capsule_and_rect() {
+        let capsule = Capsule::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let points = closest_points(
+            &capsule,
+            &Transform::from_xyz(0.0, 2.0, 0.0),
+            &rect,
+            &Transform::from_xyz(3.0, 0.0, 0.0),
+        )
+        .unwrap();
+
+        // From the end of the capsule to the upper left corner of the rect
+        let expected = Vec2::new(1.0, 1.0).distance(Vec2::new(2.0, 2.0)) - 0.5;
+        assert_abs_diff_eq!(points.distance, expected, epsilon = 1e-5);
+        assert_abs_diff_eq!(
+            Vector2::from(points.other_point),
+            Vector2::from(Vec2::new(2.0, 1.0)),
+            epsilon = 1e-5
+        );
+        assert!(!intersects(
+            &capsule,
+            &Transform::from_xyz(0.0, 2.0, 0.0),
+            &rect,
+            &Transform::from_xyz(3.0, 0.0, 0.0),
+        ));
+    }
+
+    #[test]
+    fn circles_overlap() {
+        let response = collide_convex(
+            &Circle::new(1.0),
+            &Transform::IDENTITY,
+            &Circle::new(2.0),
+            &Transform::from_xyz(0.0, 2.0, 0.0),
+        )
+        .unwrap();
+
+        // EPA approximates the round Minkowski difference by a polygon
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(Vec2::Y),
+            epsilon = 1e-2
+        );
+        assert_abs_diff_eq!(response.depth, 1.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(
+            Vector2::from(response.contacts[0].point),
+            Vector2::from(Vec2::new(0.0, 0.5)),
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn rects_overlap_like_sat() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let other_transform =
+            Transform::from_xyz(1.5, 0.5, 0.0).with_rotation(Quat::from_rotation_z(0.1));
+
+        let response =
+            collide_convex(&rect, &Transform::IDENTITY, &rect, &other_transform).unwrap();
+        let expected = rect
+            .collides(&rect, &Transform::IDENTITY, &other_transform)
+            .unwrap();
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(expected.normal),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(response.depth, expected.depth, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn capsule_in_rect_like_sat() {
+        let capsule = Capsule::new(Vec2::new(0.0, -1.0), Vec2::new(0.0, 1.0), 0.5);
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let transform =
+            Transform::from_xyz(0.3, 2.2, 0.0).with_rotation(Quat::from_rotation_z(0.4));
+
+        let response = collide_convex(&capsule, &transform, &rect, &Transform::IDENTITY).unwrap();
+        let expected = capsule
+            .collides(&rect, &transform, &Transform::IDENTITY)
+            .unwrap();
+        assert_abs_diff_eq!(
+            Vector2::from(response.normal),
+            Vector2::from(expected.normal),
+            epsilon = 1e-4
+        );
+        assert_abs_diff_eq!(response.depth, expected.depth, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn touching_or_separated_shapes_do_not_collide() {
+        let rect = polygon(Shape::rect(2.0, 2.0));
+        let segment = Segment::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0));
+
+        for y in [1.0, 1.5] {
+            assert!(collide_convex(
+                &rect,
+                &Transform::IDENTITY,
+                &segment,
+                &Transform::from_xyz(0.0, y, 0.0)
+            )
+            .is_none());
+        }
+        assert!(Shape::polyline(vec![Vec2::ZERO, Vec2::X])
+            .as_support_map()
+            .is_none());
+    }
+}
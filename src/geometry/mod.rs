@@ -1,3 +1,13 @@
+pub mod aabb;
+pub mod broad_phase;
 pub mod collider;
 pub mod collision;
+pub mod decomposition;
+pub mod filter;
+pub mod gjk;
+pub mod point_query;
+pub mod query;
+pub mod ray;
 pub mod shape;
+pub mod shape_cast;
+pub mod transform;
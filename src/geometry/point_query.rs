@@ -0,0 +1,348 @@
+use bevy::prelude::*;
+use itertools::Itertools;
+
+use crate::math::closest_point_on_segment;
+
+use super::shape::{
+    capsule::Capsule, circle::Circle, convex_polygon::ConvexPolygon, heightfield::Heightfield,
+    polyline::Polyline, segment::Segment, Shape,
+};
+
+/// Closest point on the surface of a shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointProjection {
+    /// Point on the surface
+    pub point: Vec2,
+    /// Whether the projected point lies inside of the shape
+    pub is_inside: bool,
+}
+
+/// Shape which can tell where a point lies relative to it.
+///
+/// Segments and polylines have no area and contain no points, heightfields contain the points
+/// below their surface.
+pub trait PointQuery {
+    /// Projection of the point onto the surface, in local coordinates
+    fn project_local_point(&self, point: Vec2) -> PointProjection;
+
+    /// Projection of the point onto the surface, in world coordinates.
+    ///
+    /// The projection is done in local coordinates, so it is only the closest point if the shape
+    /// is scaled uniformly.
+    fn project_point(&self, point: Vec2, transform: &Transform) -> PointProjection {
+        let local_point = transform
+            .compute_affine()
+            .inverse()
+            .transform_point3(point.extend(0.0))
+            .truncate();
+        let projection = self.project_local_point(local_point);
+        PointProjection {
+            point: transform
+                .transform_point(projection.point.extend(0.0))
+                .truncate(),
+            is_inside: projection.is_inside,
+        }
+    }
+
+    /// Whether the point in world coordinates lies inside of the shape or on its border
+    fn contains_point(&self, point: Vec2, transform: &Transform) -> bool {
+        self.project_point(point, transform).is_inside
+    }
+}
+
+impl PointQuery for Shape {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        match self {
+            Self::Circle(circle) => circle.project_local_point(point),
+            Self::ConvexPolygon(polygon) => polygon.project_local_point(point),
+            Self::Capsule(capsule) => capsule.project_local_point(point),
+            Self::Segment(segment) => segment.project_local_point(point),
+            Self::Polyline(polyline) => polyline.project_local_point(point),
+            Self::Heightfield(heightfield) => heightfield.project_local_point(point),
+            Self::Compound(shapes) => {
+                let projections: Vec<PointProjection> = shapes
+                    .iter()
+                    .map(|(transform, shape)| shape.project_point(point, &(*transform).into()))
+                    .collect();
+                // Inside of the compound if inside of any shape, projected onto the closest one
+                let is_inside = projections.iter().any(|projection| projection.is_inside);
+                closest_projection(
+                    point,
+                    projections
+                        .into_iter()
+                        .filter(|projection| projection.is_inside == is_inside),
+                )
+                // Empty compounds only have their origin
+                .unwrap_or(PointProjection {
+                    point: Vec2::ZERO,
+                    is_inside: false,
+                })
+            }
+        }
+    }
+}
+
+impl PointQuery for Circle {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        project_onto_circle(point, Vec2::ZERO, self.radius(), Vec2::X)
+    }
+}
+
+impl PointQuery for ConvexPolygon {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        let is_inside = self
+            .axes()
+            .iter()
+            .all(|&(i, _)| self.normals()[i].dot(point - self.vertices()[i]) <= 0.0);
+        let closest = self
+            .vertices()
+            .iter()
+            .circular_tuple_windows()
+            .map(|(&a, &b)| closest_point_on_segment(point, a, b))
+            .min_by(|p1, p2| {
+                point
+                    .distance_squared(*p1)
+                    .total_cmp(&point.distance_squared(*p2))
+            })
+            .expect("We cannot have empty polygons");
+
+        PointProjection {
+            point: closest,
+            is_inside,
+        }
+    }
+}
+
+impl PointQuery for Capsule {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        let (a, b) = (self.a(), self.b());
+        let center = closest_point_on_segment(point, a, b);
+        project_onto_circle(point, center, self.radius(), -(b - a).perp().normalize())
+    }
+}
+
+impl PointQuery for Segment {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        PointProjection {
+            point: closest_point_on_segment(point, self.a(), self.b()),
+            is_inside: false,
+        }
+    }
+}
+
+impl PointQuery for Polyline {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        closest_projection(
+            point,
+            self.segments().map(|(a, b)| PointProjection {
+                point: closest_point_on_segment(point, a, b),
+                is_inside: false,
+            }),
+        )
+        .expect("We cannot have empty polylines")
+    }
+}
+
+impl PointQuery for Heightfield {
+    fn project_local_point(&self, point: Vec2) -> PointProjection {
+        let last = self.heights().len() - 1;
+        let sample = (point.x + self.width() / 2.0) / self.scale();
+
+        // The closest point is at most as far away as the nearest sample
+        let nearest_sample = self.vertex(sample.round().clamp(0.0, last as f32) as usize);
+        let max_distance = point.distance(nearest_sample);
+        let closest = closest_projection(
+            point,
+            self.cells_between(point.x - max_distance, point.x + max_distance)
+                .map(|i| PointProjection {
+                    point: closest_point_on_segment(point, self.vertex(i), self.vertex(i + 1)),
+                    is_inside: false,
+                }),
+        )
+        .map_or(nearest_sample, |projection| projection.point);
+
+        // Below the surface of the cell the point lies above
+        let is_inside = (0.0..=last as f32).contains(&sample) && {
+            let cell = (sample.floor() as usize).min(last - 1);
+            let (left, right) = (self.vertex(cell), self.vertex(cell + 1));
+            (right - left).perp_dot(point - left) <= 0.0
+        };
+        PointProjection {
+            point: closest,
+            is_inside,
+        }
+    }
+}
+
+/// Projection closest to the point
+fn closest_projection(
+    point: Vec2,
+    projections: impl IntoIterator<Item = PointProjection>,
+) -> Option<PointProjection> {
+    projections.into_iter().min_by(|p1, p2| {
+        point
+            .distance_squared(p1.point)
+            .total_cmp(&point.distance_squared(p2.point))
+    })
+}
+
+/// Projection onto a circle around the center. Points at the center are projected along the
+/// fallback direction.
+fn project_onto_circle(
+    point: Vec2,
+    center: Vec2,
+    radius: f32,
+    fallback_direction: Vec2,
+) -> PointProjection {
+    let offset = point - center;
+    let direction = offset.try_normalize().unwrap_or(fallback_direction);
+    PointProjection {
+        point: center + direction * radius,
+        is_inside: offset.length_squared() <= radius * radius,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{geometry::transform::Transform2d, math::Vector2};
+
+    use super::*;
+
+    #[test]
+    fn circle_projection() {
+        let circle = Shape::circle(2.0);
+        let transform = Transform::from_xyz(1.0, 1.0, 0.0);
+
+        let outside = circle.project_point(Vec2::new(1.0, 5.0), &transform);
+        assert_eq!(outside.point, Vec2::new(1.0, 3.0));
+        assert!(!outside.is_inside);
+
+        let inside = circle.project_point(Vec2::new(0.0, 1.0), &transform);
+        assert_eq!(inside.point, Vec2::new(-1.0, 1.0));
+        assert!(inside.is_inside);
+        assert!(circle.contains_point(Vec2::new(1.0, 3.0), &transform));
+    }
+
+    #[test]
+    fn project_onto_rotated_rect() {
+        let rect = Shape::rect(2.0, 2.0);
+        let transform = Transform::from_xyz(0.0, 0.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+
+        // Corner of the diamond
+        let outside = rect.project_point(Vec2::new(3.0, 0.0), &transform);
+        assert_abs_diff_eq!(
+            Vector2::from(outside.point),
+            Vector2::from(Vec2::new(2.0f32.sqrt(), 0.0)),
+            epsilon = 1e-5
+        );
+        assert!(!outside.is_inside);
+
+        // Closest side of the diamond
+        let inside = rect.project_point(Vec2::new(0.5, 0.5), &transform);
+        let half_diagonal = 2.0f32.sqrt() / 2.0;
+        assert_abs_diff_eq!(
+            Vector2::from(inside.point),
+            Vector2::from(Vec2::splat(half_diagonal)),
+            epsilon = 1e-5
+        );
+        assert!(inside.is_inside);
+        assert!(!rect.contains_point(Vec2::new(1.2, 0.5), &transform));
+    }
+
+    #[test]
+    fn project_onto_capsule() {
+        let capsule = Shape::capsule(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+
+        let side = capsule.project_point(Vec2::new(0.5, 0.2), &Transform::IDENTITY);
+        assert_abs_diff_eq!(
+            Vector2::from(side.point),
+            Vector2::from(Vec2::new(0.5, 0.5))
+        );
+        assert!(side.is_inside);
+
+        let end = capsule.project_point(Vec2::new(3.0, 0.0), &Transform::IDENTITY);
+        assert_abs_diff_eq!(Vector2::from(end.point), Vector2::from(Vec2::new(1.5, 0.0)));
+        assert!(!end.is_inside);
+    }
+
+    #[test]
+    fn chains_contain_no_points() {
+        let polyline = Shape::polyline(vec![
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 0.0),
+        ]);
+        let projection = polyline.project_point(Vec2::new(1.0, 1.0), &Transform::IDENTITY);
+        assert_abs_diff_eq!(
+            Vector2::from(projection.point),
+            Vector2::from(Vec2::new(0.5, 0.5))
+        );
+        assert!(!projection.is_inside);
+        assert!(
+            !Shape::segment(Vec2::ZERO, Vec2::X).contains_point(Vec2::ZERO, &Transform::IDENTITY)
+        );
+    }
+
+    #[test]
+    fn heightfield_contains_points_below_surface() {
+        let heightfield = Shape::heightfield(vec![0.0, 2.0, 0.0, 0.0], 2.0);
+
+        let below = heightfield.project_point(Vec2::new(-0.5, 1.0), &Transform::IDENTITY);
+        assert!(below.is_inside);
+        // Closer to the slope on the right than to the one on the left of the peak
+        assert_abs_diff_eq!(
+            Vector2::from(below.point),
+            Vector2::from(Vec2::new(-0.25, 1.25)),
+            epsilon = 1e-5
+        );
+
+        let above = heightfield.project_point(Vec2::new(2.0, 1.0), &Transform::IDENTITY);
+        assert!(!above.is_inside);
+        assert_abs_diff_eq!(
+            Vector2::from(above.point),
+            Vector2::from(Vec2::new(2.0, 0.0))
+        );
+
+        assert!(!heightfield.contains_point(Vec2::new(4.0, -1.0), &Transform::IDENTITY));
+        assert!(heightfield.contains_point(Vec2::new(3.0, -1.0), &Transform::IDENTITY));
+    }
+
+    #[test]
+    fn point_inside_of_one_shape_of_compound() {
+        // Circle sticking out of the top of the rect
+        let compound = Shape::compound(vec![
+            (
+                Transform2d::from_translation(Vec2::new(2.0, 2.0)),
+                Shape::circle(1.0),
+            ),
+            (
+                Transform2d::from_translation(Vec2::new(2.0, 0.0)),
+                Shape::rect(4.0, 4.0),
+            ),
+        ]);
+
+        // Closer to the border of the circle, but only inside of the rect
+        let projection = compound.project_point(Vec2::new(2.0, 0.5), &Transform::IDENTITY);
+        assert!(projection.is_inside);
+        assert_abs_diff_eq!(
+            Vector2::from(projection.point),
+            Vector2::from(Vec2::new(2.0, 2.0))
+        );
+        assert!(compound.contains_point(Vec2::new(2.0, 2.8), &Transform::IDENTITY));
+        assert!(!compound.contains_point(Vec2::new(2.0, 3.5), &Transform::IDENTITY));
+    }
+
+    #[test]
+    fn empty_compound_contains_no_points() {
+        let compound = Shape::compound(vec![]);
+
+        let transform = Transform::from_xyz(1.0, 2.0, 0.0);
+        let projection = compound.project_point(Vec2::new(1.0, 3.0), &transform);
+        assert_eq!(projection.point, Vec2::new(1.0, 2.0));
+        assert!(!projection.is_inside);
+        assert!(!compound.contains_point(Vec2::new(1.0, 2.0), &transform));
+    }
+}
@@ -0,0 +1,322 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use super::{
+    aabb::Aabb,
+    broad_phase::BroadPhase,
+    collider::Collider,
+    collision::CollisionWith,
+    filter::{CollisionGroups, QueryFilter},
+    point_query::PointQuery,
+    ray::{Ray, RayCast, RayHit},
+    shape::Shape,
+    shape_cast::{cast_shape, swept_aabb, ShapeHit},
+};
+
+/// Answers questions about the colliders in the world, e.g. what a ray hits.
+///
+/// Candidates are looked up by the bounds the [`BroadPhase`] stored at the end of the last physics
+/// step. Colliders spawned or moved outside of the physics step are found at their new place
+/// after the next step.
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    broad_phase: Res<'w, BroadPhase>,
+    colliders: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Collider,
+            &'static Transform,
+            Option<&'static CollisionGroups>,
+        ),
+    >,
+}
+
+impl SpatialQuery<'_, '_> {
+    /// First collider hit by the ray up to the maximal time of impact, see [`RayCast`]
+    pub fn cast_ray(
+        &self,
+        ray: &Ray,
+        max_toi: f32,
+        filter: &QueryFilter,
+    ) -> Option<(Entity, RayHit)> {
+        self.candidates(self.broad_phase.query_ray(*ray, max_toi), filter)
+            .filter_map(|(entity, collider, transform)| {
+                let hit = collider.shape.cast_ray(ray, transform, max_toi)?;
+                Some((entity, hit))
+            })
+            .min_by(|(_, hit1), (_, hit2)| hit1.toi.total_cmp(&hit2.toi))
+    }
+
+    /// First collider hit by the shape moving from the transform with the velocity up to the
+    /// maximal time of impact, see [`cast_shape`]
+    pub fn cast_shape(
+        &self,
+        shape: &Shape,
+        transform: &Transform,
+        velocity: Vec2,
+        max_toi: f32,
+        filter: &QueryFilter,
+    ) -> Option<(Entity, ShapeHit)> {
+        let swept_aabb = swept_aabb(shape, transform, velocity, max_toi);
+        self.candidates_in_aabb(swept_aabb, filter)
+            .filter_map(|(entity, collider, other_transform)| {
+                let hit = cast_shape(
+                    shape,
+                    transform,
+                    velocity,
+                    &collider.shape,
+                    other_transform,
+                    max_toi,
+                )?;
+                Some((entity, hit))
+            })
+            .min_by(|(_, hit1), (_, hit2)| hit1.toi.total_cmp(&hit2.toi))
+    }
+
+    /// All colliders containing the point, see [`PointQuery`]
+    pub fn intersections_with_point(&self, point: Vec2, filter: &QueryFilter) -> Vec<Entity> {
+        self.candidates_in_aabb(Aabb::new(point, point), filter)
+            .filter(|(_, collider, transform)| collider.shape.contains_point(point, transform))
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    /// All colliders overlapping the shape with the given transform
+    pub fn intersections_with_shape(
+        &self,
+        shape: &Shape,
+        transform: &Transform,
+        filter: &QueryFilter,
+    ) -> Vec<Entity> {
+        self.candidates_in_aabb(shape.aabb(transform), filter)
+            .filter(|(_, collider, other_transform)| {
+                shape
+                    .collides(&collider.shape, transform, other_transform)
+                    .is_some()
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    /// All colliders whose bounding boxes overlap or touch the box
+    pub fn colliders_in_aabb(&self, aabb: Aabb) -> Vec<Entity> {
+        self.candidates_in_aabb(aabb, &QueryFilter::default())
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    /// Colliders passing the filter whose bounds overlap or touch the box
+    fn candidates_in_aabb<'a>(
+        &'a self,
+        aabb: Aabb,
+        filter: &'a QueryFilter,
+    ) -> impl Iterator<Item = (Entity, &'a Collider, &'a Transform)> + 'a {
+        self.candidates(self.broad_phase.query_aabb(aabb), filter)
+    }
+
+    /// Colliders found in the broad phase which pass the filter
+    fn candidates<'a>(
+        &'a self,
+        entities: impl Iterator<Item = (Entity, CollisionGroups)> + 'a,
+        filter: &'a QueryFilter,
+    ) -> impl Iterator<Item = (Entity, &'a Collider, &'a Transform)> + 'a {
+        entities
+            // Colliders removed since the last physics step are skipped
+            .filter_map(|(entity, _)| self.colliders.get(entity).ok())
+            .filter(|(entity, _, _, groups)| {
+                filter.accepts(*entity, &groups.copied().unwrap_or_default())
+            })
+            .map(|(entity, collider, transform, _)| (entity, collider, transform))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use crate::geometry::{broad_phase::update_broad_phase, filter::Group};
+
+    use super::*;
+
+    /// Stores the bounds of the colliders in the broad phase like a physics step
+    fn spatial_query(world: &mut World) -> SystemState<SpatialQuery<'static, 'static>> {
+        world.init_resource::<BroadPhase>();
+        let mut schedule = Schedule::new();
+        schedule.add_system(update_broad_phase);
+        schedule.run(world);
+        SystemState::new(world)
+    }
+
+    #[test]
+    fn ray_hits_first_collider_of_interacting_groups() {
+        let mut world = World::new();
+        let shooter = world
+            .spawn((Collider::circle(1.0), Transform::IDENTITY))
+            .id();
+        let ghost = world
+            .spawn((
+                Collider::rect(2.0, 2.0),
+                Transform::from_xyz(3.0, 0.0, 0.0),
+                CollisionGroups::new(Group::GROUP_2, Group::GROUP_2),
+            ))
+            .id();
+        let wall = world
+            .spawn((Collider::rect(2.0, 2.0), Transform::from_xyz(6.0, 0.0, 0.0)))
+            .id();
+        world.spawn((Collider::rect(2.0, 2.0), Transform::from_xyz(9.0, 0.0, 0.0)));
+
+        let mut state = spatial_query(&mut world);
+        let query = state.get(&world);
+        let ray = Ray::new(Vec2::ZERO, Vec2::X);
+
+        let filter = QueryFilter::new(CollisionGroups::new(Group::GROUP_1, Group::ALL))
+            .with_excluded(shooter);
+        let (entity, hit) = query.cast_ray(&ray, f32::MAX, &filter).unwrap();
+        assert_eq!(entity, wall);
+        assert_eq!(hit.toi, 5.0);
+
+        let (entity, hit) = query
+            .cast_ray(&ray, f32::MAX, &QueryFilter::default())
+            .unwrap();
+        assert_eq!(entity, shooter);
+        assert_eq!(hit.toi, 0.0);
+
+        let filter = QueryFilter::default().with_excluded(shooter);
+        assert_eq!(query.cast_ray(&ray, f32::MAX, &filter).unwrap().0, ghost);
+        assert!(query.cast_ray(&ray, 1.5, &filter).is_none());
+    }
+
+    #[test]
+    fn colliders_containing_point() {
+        let mut world = World::new();
+        let ground = world
+            .spawn((Collider::rect(10.0, 2.0), Transform::IDENTITY))
+            .id();
+        let ball = world
+            .spawn((Collider::circle(1.0), Transform::from_xyz(1.0, 1.0, 0.0)))
+            .id();
+        world.spawn((Collider::circle(1.0), Transform::from_xyz(-3.0, 1.0, 0.0)));
+
+        let mut state = spatial_query(&mut world);
+        let query = state.get(&world);
+        let mut entities =
+            query.intersections_with_point(Vec2::new(1.0, 0.5), &QueryFilter::default());
+        entities.sort();
+        assert_eq!(entities, [ground, ball]);
+
+        let filter = QueryFilter::default().with_excluded(ground);
+        assert_eq!(
+            query.intersections_with_point(Vec2::new(1.0, 0.5), &filter),
+            [ball]
+        );
+        assert!(query
+            .intersections_with_point(Vec2::new(0.0, 5.0), &QueryFilter::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn shape_hits_first_collider_on_its_way() {
+        let mut world = World::new();
+        world.spawn((Collider::rect(10.0, 1.0), Transform::IDENTITY));
+        let platform = world
+            .spawn((Collider::rect(2.0, 1.0), Transform::from_xyz(0.0, 3.0, 0.0)))
+            .id();
+        world.spawn((Collider::circle(1.0), Transform::from_xyz(3.0, 3.0, 0.0)));
+
+        let mut state = spatial_query(&mut world);
+        let query = state.get(&world);
+        let (entity, hit) = query
+            .cast_shape(
+                &Shape::circle(0.5),
+                &Transform::from_xyz(0.5, 10.0, 0.0),
+                -Vec2::Y,
+                f32::MAX,
+                &QueryFilter::default(),
+            )
+            .unwrap();
+
+        assert_eq!(entity, platform);
+        assert!((hit.toi - 6.0).abs() < 1e-3);
+        assert!(hit.normal.dot(Vec2::Y) > 0.999);
+    }
+
+    #[test]
+    fn shapes_and_boxes_find_overlapping_colliders() {
+        let mut world = World::new();
+        let ground = world
+            .spawn((Collider::rect(10.0, 2.0), Transform::IDENTITY))
+            .id();
+        let ball = world
+            .spawn((Collider::circle(1.0), Transform::from_xyz(2.0, 2.5, 0.0)))
+            .id();
+        let block = world
+            .spawn((
+                Collider::rect(1.0, 1.0),
+                Transform::from_xyz(-3.0, 1.5, 0.0),
+            ))
+            .id();
+
+        let mut state = spatial_query(&mut world);
+        let query = state.get(&world);
+
+        // Cone of vision of an enemy looking to the right
+        let cone =
+            Shape::convex_polygon(vec![Vec2::ZERO, Vec2::new(4.0, -2.0), Vec2::new(4.0, 3.0)]);
+        let mut entities = query.intersections_with_shape(
+            &cone,
+            &Transform::from_xyz(0.0, 2.0, 0.0),
+            &QueryFilter::default(),
+        );
+        entities.sort();
+        assert_eq!(entities, [ground, ball]);
+
+        // Bounds of the ball touch the box, although the circle does not
+        let mut entities =
+            query.colliders_in_aabb(Aabb::new(Vec2::new(0.9, 3.0), Vec2::new(1.1, 5.0)));
+        entities.sort();
+        assert_eq!(entities, [ball]);
+
+        let mut entities =
+            query.colliders_in_aabb(Aabb::new(Vec2::new(-4.0, 0.5), Vec2::new(3.0, 1.5)));
+        entities.sort();
+        assert_eq!(entities, [ground, ball, block]);
+    }
+
+    #[test]
+    fn only_colliders_in_broad_phase_are_visited() {
+        let mut world = World::new();
+        let ground = world
+            .spawn((Collider::rect(10.0, 2.0), Transform::IDENTITY))
+            .id();
+        let far = world
+            .spawn((Collider::circle(1.0), Transform::from_xyz(50.0, 0.0, 0.0)))
+            .id();
+        let mut state = spatial_query(&mut world);
+
+        // Moved and spawned next to the ground between physics steps
+        world.get_mut::<Transform>(far).unwrap().translation = Vec3::new(2.0, 0.0, 0.0);
+        let spawned = world
+            .spawn((Collider::circle(1.0), Transform::from_xyz(-2.0, 0.0, 0.0)))
+            .id();
+
+        // Neither is visited, the broad phase only knows the far away bounds of the moved collider
+        let aabb = Aabb::new(Vec2::new(-5.0, -1.0), Vec2::new(5.0, 1.0));
+        let query = state.get(&world);
+        assert_eq!(query.colliders_in_aabb(aabb), [ground]);
+        assert!(query
+            .cast_ray(
+                &Ray::new(Vec2::new(-10.0, 0.0), Vec2::X),
+                20.0,
+                &QueryFilter::default().with_excluded(ground)
+            )
+            .is_none());
+
+        // Found after the next physics step
+        let mut state = spatial_query(&mut world);
+        let query = state.get(&world);
+        let mut entities = query.colliders_in_aabb(aabb);
+        entities.sort();
+        assert_eq!(entities, [ground, far, spawned]);
+    }
+}
@@ -0,0 +1,407 @@
+use bevy::prelude::*;
+
+use crate::math::closest_point_on_segment;
+
+use super::shape::{
+    capsule::Capsule, circle::Circle, convex_polygon::ConvexPolygon, heightfield::Heightfield,
+    polyline::Polyline, segment::Segment, Shape,
+};
+
+/// Half-line starting at `origin`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2,
+    /// Distance travelled per unit of time of impact
+    pub direction: Vec2,
+}
+
+impl Ray {
+    pub fn new(origin: Vec2, direction: Vec2) -> Self {
+        Self { origin, direction }
+    }
+
+    /// Point of the ray at the time of impact
+    pub fn point_at(&self, toi: f32) -> Vec2 {
+        self.origin + self.direction * toi
+    }
+}
+
+/// Where a ray hits a shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// Time of impact, the hit point is [`Ray::point_at`] it. With a normalized direction this is
+    /// the distance to the origin of the ray.
+    pub toi: f32,
+    /// Normal of the hit surface, pointing against the ray
+    pub normal: Vec2,
+}
+
+/// Shape which can be hit by rays.
+///
+/// Shapes are solid, a ray starting inside of one hits it immediately with a normal against the
+/// ray. Chains are only hit from their colliding side.
+pub trait RayCast {
+    /// First hit of the ray in local coordinates up to the maximal time of impact
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit>;
+
+    /// First hit of the ray in world coordinates up to the maximal time of impact
+    fn cast_ray(&self, ray: &Ray, transform: &Transform, max_toi: f32) -> Option<RayHit> {
+        // Transforming the direction along keeps the time of impact the same
+        let inverse = transform.compute_affine().inverse();
+        let local_ray = Ray::new(
+            inverse.transform_point3(ray.origin.extend(0.0)).truncate(),
+            inverse
+                .transform_vector3(ray.direction.extend(0.0))
+                .truncate(),
+        );
+        let hit = self.cast_local_ray(&local_ray, max_toi)?;
+
+        // Normals are scaled inversely to stay perpendicular to the scaled surface
+        let normal = (transform.rotation * (hit.normal / transform.scale.truncate()).extend(0.0))
+            .truncate()
+            .normalize_or_zero();
+        Some(RayHit {
+            toi: hit.toi,
+            normal,
+        })
+    }
+}
+
+impl RayCast for Shape {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        match self {
+            Self::Circle(circle) => circle.cast_local_ray(ray, max_toi),
+            Self::ConvexPolygon(polygon) => polygon.cast_local_ray(ray, max_toi),
+            Self::Capsule(capsule) => capsule.cast_local_ray(ray, max_toi),
+            Self::Segment(segment) => segment.cast_local_ray(ray, max_toi),
+            Self::Polyline(polyline) => polyline.cast_local_ray(ray, max_toi),
+            Self::Heightfield(heightfield) => heightfield.cast_local_ray(ray, max_toi),
+            Self::Compound(shapes) => first_hit(shapes.iter().filter_map(|(transform, shape)| {
+                shape.cast_ray(ray, &(*transform).into(), max_toi)
+            })),
+        }
+    }
+}
+
+impl RayCast for Circle {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        cast_ray_circle(ray, Vec2::ZERO, self.radius(), max_toi)
+    }
+}
+
+impl RayCast for ConvexPolygon {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        // Cut off the parts of the ray outside of every edge, what is left is inside
+        let (mut enter, mut exit) = (0.0, max_toi);
+        let mut normal = None;
+        for &(i, _) in self.axes() {
+            let (vertex, edge_normal) = (self.vertices()[i], self.normals()[i]);
+            // Positive if the origin lies inside of the edge
+            let distance = edge_normal.dot(vertex - ray.origin);
+            let approach = edge_normal.dot(ray.direction);
+            if approach == 0.0 {
+                if distance < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let toi = distance / approach;
+            if approach < 0.0 {
+                if toi > enter {
+                    enter = toi;
+                    normal = Some(edge_normal);
+                }
+            } else if toi < exit {
+                exit = toi;
+            }
+            if enter > exit {
+                return None;
+            }
+        }
+
+        Some(RayHit {
+            toi: enter,
+            normal: normal.unwrap_or_else(|| -ray.direction.normalize_or_zero()),
+        })
+    }
+}
+
+impl RayCast for Capsule {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        let (a, b, radius) = (self.a(), self.b(), self.radius());
+        if closest_point_on_segment(ray.origin, a, b).distance(ray.origin) <= radius {
+            return Some(RayHit {
+                toi: 0.0,
+                normal: -ray.direction.normalize_or_zero(),
+            });
+        }
+
+        // The ray enters through one of the half circles or one of the straight sides
+        let offset = (b - a).perp().normalize() * radius;
+        first_hit(
+            [
+                cast_ray_circle(ray, a, radius, max_toi),
+                cast_ray_circle(ray, b, radius, max_toi),
+                cast_ray_segment(ray, a + offset, b + offset, false, max_toi),
+                cast_ray_segment(ray, a - offset, b - offset, false, max_toi),
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+}
+
+impl RayCast for Segment {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        cast_ray_segment(ray, self.a(), self.b(), false, max_toi)
+    }
+}
+
+impl RayCast for Polyline {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        first_hit(
+            self.segments()
+                .filter_map(|(a, b)| cast_ray_segment(ray, a, b, self.is_one_sided(), max_toi)),
+        )
+    }
+}
+
+impl RayCast for Heightfield {
+    fn cast_local_ray(&self, ray: &Ray, max_toi: f32) -> Option<RayHit> {
+        let end_x = if ray.direction.x == 0.0 {
+            ray.origin.x
+        } else {
+            ray.point_at(max_toi).x
+        };
+        // Cells from right to left, so the top of the surface is the colliding side
+        first_hit(
+            self.cells_between(ray.origin.x.min(end_x), ray.origin.x.max(end_x))
+                .filter_map(|i| {
+                    cast_ray_segment(ray, self.vertex(i + 1), self.vertex(i), true, max_toi)
+                }),
+        )
+    }
+}
+
+/// Hit with the smallest time of impact
+fn first_hit(hits: impl IntoIterator<Item = RayHit>) -> Option<RayHit> {
+    hits.into_iter()
+        .min_by(|hit1, hit2| hit1.toi.total_cmp(&hit2.toi))
+}
+
+/// First hit of the ray with a circle around the center
+fn cast_ray_circle(ray: &Ray, center: Vec2, radius: f32, max_toi: f32) -> Option<RayHit> {
+    let offset = ray.origin - center;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(RayHit {
+            toi: 0.0,
+            normal: -ray.direction.normalize_or_zero(),
+        });
+    }
+
+    // Solve |offset + toi * direction| = radius for the smaller toi
+    let a = ray.direction.length_squared();
+    let b = offset.dot(ray.direction);
+    if b >= 0.0 {
+        // Moving away from the circle
+        return None;
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let toi = (-b - discriminant.sqrt()) / a;
+    if toi > max_toi {
+        return None;
+    }
+
+    Some(RayHit {
+        toi,
+        normal: (ray.point_at(toi) - center).normalize(),
+    })
+}
+
+/// Hit of the ray with the segment from `a` to `b`.
+///
+/// One-sided segments are only hit from the right, as seen along the segment.
+fn cast_ray_segment(ray: &Ray, a: Vec2, b: Vec2, one_sided: bool, max_toi: f32) -> Option<RayHit> {
+    let edge = b - a;
+    let denominator = ray.direction.perp_dot(edge);
+    if denominator == 0.0 {
+        // Parallel to the segment
+        return None;
+    }
+
+    // Solve origin + toi * direction = a + t * edge
+    let toi = (a - ray.origin).perp_dot(edge) / denominator;
+    let t = (a - ray.origin).perp_dot(ray.direction) / denominator;
+    if toi < 0.0 || toi > max_toi || !(0.0..=1.0).contains(&t) {
+        return None;
+    }
+
+    let right = -edge.perp().normalize();
+    let facing = right.dot(ray.direction) < 0.0;
+    if one_sided && !facing {
+        return None;
+    }
+    Some(RayHit {
+        toi,
+        normal: if facing { right } else { -right },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{geometry::transform::Transform2d, math::Vector2};
+
+    use super::*;
+
+    #[test]
+    fn ray_hits_circle() {
+        let ray = Ray::new(Vec2::new(-5.0, 1.0), Vec2::X);
+        let hit = Shape::circle(2.0)
+            .cast_ray(&ray, &Transform::from_xyz(1.0, 1.0, 0.0), f32::MAX)
+            .unwrap();
+
+        assert_abs_diff_eq!(hit.toi, 4.0);
+        assert_abs_diff_eq!(Vector2::from(hit.normal), Vector2::from(-Vec2::X));
+
+        assert!(Shape::circle(2.0)
+            .cast_ray(&ray, &Transform::from_xyz(1.0, 1.0, 0.0), 3.0)
+            .is_none());
+        assert!(Shape::circle(2.0)
+            .cast_ray(&ray, &Transform::from_xyz(1.0, 4.0, 0.0), f32::MAX)
+            .is_none());
+    }
+
+    #[test]
+    fn ray_hits_rotated_rect() {
+        // Diamond with its corners on the axes
+        let transform = Transform::from_xyz(0.0, 0.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+        let ray = Ray::new(Vec2::new(0.5, 5.0), -Vec2::Y);
+        let hit = Shape::rect(2.0, 2.0)
+            .cast_ray(&ray, &transform, f32::MAX)
+            .unwrap();
+
+        assert_abs_diff_eq!(hit.toi, 5.0 - (2.0f32.sqrt() - 0.5), epsilon = 1e-5);
+        assert_abs_diff_eq!(
+            Vector2::from(hit.normal),
+            Vector2::from(Vec2::ONE.normalize()),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn ray_inside_polygon_hits_immediately() {
+        let ray = Ray::new(Vec2::new(0.2, 0.3), Vec2::X);
+        let hit = Shape::rect(2.0, 2.0)
+            .cast_ray(&ray, &Transform::IDENTITY, f32::MAX)
+            .unwrap();
+
+        assert_eq!(hit.toi, 0.0);
+        assert_eq!(hit.normal, -Vec2::X);
+    }
+
+    #[test]
+    fn ray_hits_side_and_end_of_capsule() {
+        let capsule = Shape::capsule(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), 0.5);
+
+        let hit = capsule
+            .cast_ray(
+                &Ray::new(Vec2::new(0.5, 3.0), -Vec2::Y),
+                &Transform::IDENTITY,
+                10.0,
+            )
+            .unwrap();
+        assert_abs_diff_eq!(hit.toi, 2.5);
+        assert_abs_diff_eq!(Vector2::from(hit.normal), Vector2::from(Vec2::Y));
+
+        let hit = capsule
+            .cast_ray(
+                &Ray::new(Vec2::new(5.0, 0.0), -Vec2::X),
+                &Transform::IDENTITY,
+                10.0,
+            )
+            .unwrap();
+        assert_abs_diff_eq!(hit.toi, 3.5);
+        assert_abs_diff_eq!(Vector2::from(hit.normal), Vector2::from(Vec2::X));
+    }
+
+    #[test]
+    fn one_sided_polyline_is_hit_from_the_right() {
+        let polyline = Shape::one_sided_polyline(vec![
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(-1.0, 0.0),
+        ]);
+        let down = Ray::new(Vec2::new(0.5, 1.0), -Vec2::Y);
+        let up = Ray::new(Vec2::new(0.5, -1.0), Vec2::Y);
+
+        let hit = polyline
+            .cast_ray(&down, &Transform::IDENTITY, f32::MAX)
+            .unwrap();
+        assert_eq!(hit.toi, 1.0);
+        assert_eq!(hit.normal, Vec2::Y);
+        assert!(polyline
+            .cast_ray(&up, &Transform::IDENTITY, f32::MAX)
+            .is_none());
+        assert!(
+            Shape::polyline(vec![Vec2::new(1.0, 0.0), Vec2::new(-1.0, 0.0)])
+                .cast_ray(&up, &Transform::IDENTITY, f32::MAX)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn ray_hits_heightfield_from_above() {
+        let heightfield = Shape::heightfield(vec![0.0, 1.0, 0.0, 2.0, 0.0], 1.0);
+        let ray = Ray::new(Vec2::new(-1.0, 3.0), Vec2::new(1.0, -2.0));
+
+        // Passes over the valley and hits the slope of the second peak
+        let hit = heightfield
+            .cast_ray(&ray, &Transform::IDENTITY, f32::MAX)
+            .unwrap();
+        let point = ray.point_at(hit.toi);
+        assert_abs_diff_eq!(
+            Vector2::from(point),
+            Vector2::from(Vec2::new(0.25, 0.5)),
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(hit.normal),
+            Vector2::from(Vec2::new(-2.0, 1.0).normalize()),
+            epsilon = 1e-5
+        );
+
+        let from_below = Ray::new(Vec2::new(0.5, -5.0), Vec2::Y);
+        assert!(heightfield
+            .cast_ray(&from_below, &Transform::IDENTITY, f32::MAX)
+            .is_none());
+    }
+
+    #[test]
+    fn ray_hits_nearest_shape_of_compound() {
+        let compound = Shape::compound(vec![
+            (
+                Transform2d::from_translation(Vec2::new(0.0, 3.0)),
+                Shape::circle(1.0),
+            ),
+            (
+                Transform2d::from_translation(Vec2::new(0.0, -3.0)),
+                Shape::rect(2.0, 2.0),
+            ),
+        ]);
+        let ray = Ray::new(Vec2::new(0.0, -10.0), Vec2::Y);
+
+        let hit = compound
+            .cast_ray(&ray, &Transform::from_xyz(0.0, 1.0, 0.0), f32::MAX)
+            .unwrap();
+        assert_abs_diff_eq!(hit.toi, 7.0);
+        assert_abs_diff_eq!(Vector2::from(hit.normal), Vector2::from(-Vec2::Y));
+    }
+}
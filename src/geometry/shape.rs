@@ -1,15 +1,40 @@
-use bevy::prelude::Vec2;
+use bevy::prelude::{Transform, Vec2};
 use enum_as_inner::EnumAsInner;
+use itertools::Itertools;
 
-use self::{circle::Circle, convex_polygon::ConvexPolygon};
+use self::{
+    capsule::Capsule,
+    circle::Circle,
+    convex_polygon::{ConvexPolygon, ConvexPolygonError},
+    heightfield::Heightfield,
+    polyline::Polyline,
+    segment::Segment,
+};
 
+use super::{
+    aabb::Aabb,
+    decomposition::{decompose_polygon, is_self_intersecting},
+    gjk::SupportMap,
+    transform::Transform2d,
+};
+
+pub mod capsule;
 pub mod circle;
 pub mod convex_polygon;
+pub mod heightfield;
+pub mod polyline;
+pub mod segment;
 
 #[derive(EnumAsInner)]
 pub enum Shape {
     Circle(Circle),
     ConvexPolygon(ConvexPolygon),
+    Capsule(Capsule),
+    Segment(Segment),
+    Polyline(Polyline),
+    Heightfield(Heightfield),
+    /// Several shapes placed relative to the collider, acting as one
+    Compound(Vec<(Transform2d, Shape)>),
 }
 
 impl Shape {
@@ -17,40 +42,170 @@ impl Shape {
         Self::Circle(Circle::new(radius))
     }
 
+    /// Convex polygon through the vertices, see [`ConvexPolygon::new`]
+    ///
+    /// # Panics
+    ///
+    /// If the vertices do not describe a convex polygon
     pub fn convex_polygon(vertices: Vec<Vec2>) -> Self {
         Self::ConvexPolygon(ConvexPolygon::new(vertices))
     }
 
-    pub fn rect(width: f32, height: f32) -> Self {
-        let width_half = width / 2.0;
-        let height_half = height / 2.0;
+    /// Convex polygon through the vertices, see [`ConvexPolygon::try_new`]
+    pub fn try_convex_polygon(vertices: Vec<Vec2>) -> Result<Self, ConvexPolygonError> {
+        ConvexPolygon::try_new(vertices).map(Self::ConvexPolygon)
+    }
 
-        let vertices = vec![
-            Vec2::new(width_half, height_half),
-            Vec2::new(width_half, -height_half),
-            Vec2::new(-width_half, -height_half),
-            Vec2::new(-width_half, height_half),
-        ];
+    /// Convex hull of the points, see [`ConvexPolygon::try_from_hull`]
+    pub fn convex_hull(points: &[Vec2]) -> Result<Self, ConvexPolygonError> {
+        ConvexPolygon::try_from_hull(points).map(Self::ConvexPolygon)
+    }
 
-        Self::ConvexPolygon(ConvexPolygon::new(vertices))
+    /// Shape of a simple polygon, which may be concave.
+    ///
+    /// Concave polygons are split into a [`Shape::Compound`] of convex pieces, convex ones stay
+    /// a single [`Shape::ConvexPolygon`]. Fails if there are less than three different vertices,
+    /// the edges intersect each other or the polygon has no area.
+    pub fn concave_polygon(vertices: Vec<Vec2>) -> Result<Self, ConvexPolygonError> {
+        if vertices.len() < 3 {
+            return Err(ConvexPolygonError::TooFewVertices(vertices.len()));
+        }
+        if let Some((i, j)) = (0..vertices.len())
+            .tuple_combinations()
+            .find(|&(i, j)| vertices[i] == vertices[j])
+        {
+            return Err(ConvexPolygonError::DuplicateVertex(i, j));
+        }
+        if is_self_intersecting(&vertices) {
+            return Err(ConvexPolygonError::SelfIntersecting);
+        }
+
+        // Slivers without area are left out
+        let mut pieces: Vec<ConvexPolygon> = decompose_polygon(&vertices)
+            .into_iter()
+            .filter_map(|piece| ConvexPolygon::try_new(piece).ok())
+            .collect();
+        match pieces.len() {
+            0 => Err(ConvexPolygonError::Degenerate),
+            1 => Ok(Self::ConvexPolygon(pieces.remove(0))),
+            _ => Ok(Self::Compound(
+                pieces
+                    .into_iter()
+                    .map(|piece| (Transform2d::default(), Self::ConvexPolygon(piece)))
+                    .collect(),
+            )),
+        }
+    }
+
+    pub fn capsule(a: Vec2, b: Vec2, radius: f32) -> Self {
+        Self::Capsule(Capsule::new(a, b, radius))
+    }
+
+    pub fn segment(a: Vec2, b: Vec2) -> Self {
+        Self::Segment(Segment::new(a, b))
+    }
+
+    pub fn polyline(vertices: Vec<Vec2>) -> Self {
+        Self::Polyline(Polyline::new(vertices))
+    }
+
+    pub fn one_sided_polyline(vertices: Vec<Vec2>) -> Self {
+        Self::Polyline(Polyline::one_sided(vertices))
+    }
+
+    pub fn heightfield(heights: Vec<f32>, scale: f32) -> Self {
+        Self::Heightfield(Heightfield::new(heights, scale))
     }
 
+    pub fn compound(shapes: Vec<(Transform2d, Shape)>) -> Self {
+        Self::Compound(shapes)
+    }
+
+    /// # Panics
+    ///
+    /// If the width or height is zero
+    pub fn rect(width: f32, height: f32) -> Self {
+        Self::convex_polygon(rect_vertices(width, height))
+    }
+
+    /// Rectangle centered at the origin, fails if the width or height is zero
+    pub fn try_rect(width: f32, height: f32) -> Result<Self, ConvexPolygonError> {
+        Self::try_convex_polygon(rect_vertices(width, height))
+    }
+
+    /// # Panics
+    ///
+    /// If there are less than three sides or the radius is zero
     pub fn regular_polygon(radius: f32, sides: usize) -> Self {
-        let mut vertices: Vec<Vec2> = Vec::new();
+        Self::convex_polygon(regular_polygon_vertices(radius, sides))
+    }
 
-        let dphi = 360.0 / sides as f64;
-        for s in 0..sides {
-            let phi = (90.0 + s as f64 * dphi).to_radians();
-            vertices.push(radius * Vec2::new(phi.cos() as f32, phi.sin() as f32));
+    /// Regular polygon with a corner at the top, fails if there are less than three sides or the
+    /// radius is zero
+    pub fn try_regular_polygon(radius: f32, sides: usize) -> Result<Self, ConvexPolygonError> {
+        Self::try_convex_polygon(regular_polygon_vertices(radius, sides))
+    }
+
+    /// Bounding box of the shape in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        match self {
+            Self::Circle(circle) => circle.aabb(transform),
+            Self::ConvexPolygon(polygon) => polygon.aabb(transform),
+            Self::Capsule(capsule) => capsule.aabb(transform),
+            Self::Segment(segment) => segment.aabb(transform),
+            Self::Polyline(polyline) => polyline.aabb(transform),
+            Self::Heightfield(heightfield) => heightfield.aabb(transform),
+            Self::Compound(shapes) => shapes
+                .iter()
+                .map(|(child_transform, shape)| {
+                    shape.aabb(&transform.mul_transform((*child_transform).into()))
+                })
+                .reduce(|aabb, other| aabb.merged(&other))
+                .unwrap_or_else(|| Aabb::from_center(transform.translation.truncate(), Vec2::ZERO)),
         }
+    }
 
-        Self::ConvexPolygon(ConvexPolygon::new(vertices))
+    /// The shape as [`SupportMap`] for the generic algorithms in [`gjk`](super::gjk), if it is
+    /// convex
+    pub fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        match self {
+            Self::Circle(circle) => Some(circle),
+            Self::ConvexPolygon(polygon) => Some(polygon),
+            Self::Capsule(capsule) => Some(capsule),
+            Self::Segment(segment) => Some(segment),
+            Self::Polyline(_) | Self::Heightfield(_) | Self::Compound(_) => None,
+        }
+    }
+}
+
+fn rect_vertices(width: f32, height: f32) -> Vec<Vec2> {
+    let width_half = width / 2.0;
+    let height_half = height / 2.0;
+
+    vec![
+        Vec2::new(width_half, height_half),
+        Vec2::new(-width_half, height_half),
+        Vec2::new(-width_half, -height_half),
+        Vec2::new(width_half, -height_half),
+    ]
+}
+
+fn regular_polygon_vertices(radius: f32, sides: usize) -> Vec<Vec2> {
+    let mut vertices: Vec<Vec2> = Vec::new();
+
+    let dphi = 360.0 / sides as f64;
+    for s in 0..sides {
+        let phi = (90.0 + s as f64 * dphi).to_radians();
+        vertices.push(radius * Vec2::new(phi.cos() as f32, phi.sin() as f32));
     }
+
+    vertices
 }
 
 #[cfg(test)]
 mod tests {
     use approx::assert_abs_diff_eq;
+    use bevy::prelude::{Quat, Vec3};
 
     use crate::math::Vector2;
 
@@ -59,12 +214,12 @@ mod tests {
     #[test]
     fn rect() {
         // (-w/2,  h/2)__________________(w/2,  h/2)
-        //     [3]     |                |    [0]
+        //     [1]     |                |    [0]
         //             |                |
         //             |     (0,0)      |
         //             |                |
         //             |                |
-        //     [2]     |________________|    [1]
+        //     [2]     |________________|    [3]
         // (-w/2, -h/2)                  (w/2, -h/2)
         let (w, h) = (20.0, 100.0);
         let vertices = Shape::rect(w, h)
@@ -76,9 +231,9 @@ mod tests {
             vertices,
             [
                 Vec2::new(w / 2.0, h / 2.0),
-                Vec2::new(w / 2.0, -h / 2.0),
-                Vec2::new(-w / 2.0, -h / 2.0),
                 Vec2::new(-w / 2.0, h / 2.0),
+                Vec2::new(-w / 2.0, -h / 2.0),
+                Vec2::new(w / 2.0, -h / 2.0),
             ]
         );
     }
@@ -89,7 +244,7 @@ mod tests {
         let radius = 2.0;
         let phi = 2.0 * std::f64::consts::PI / sides as f64;
         let phi = phi - std::f64::consts::FRAC_PI_2;
-        let vertices = vec![
+        let vertices = [
             radius * Vec2::new(0.0, 1.0),
             radius * Vec2::new(-phi.cos() as f32, -phi.sin() as f32),
             radius * Vec2::new(phi.cos() as f32, -phi.sin() as f32),
@@ -128,4 +283,323 @@ mod tests {
             assert_abs_diff_eq!(vertices[i].y, shape_vertices[i].y);
         }
     }
+
+    #[test]
+    fn aabb_rotated_rect() {
+        let transform = Transform::from_xyz(1.0, 2.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+        let aabb = Shape::rect(2.0, 2.0).aabb(&transform);
+
+        let diag = 2.0_f32.sqrt();
+        assert_abs_diff_eq!(
+            Vector2::from(aabb.min),
+            Vector2::from(Vec2::new(1.0 - diag, 2.0 - diag))
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(aabb.max),
+            Vector2::from(Vec2::new(1.0 + diag, 2.0 + diag))
+        );
+    }
+
+    #[test]
+    fn aabb_scaled_circle() {
+        let transform = Transform::from_xyz(-1.0, 0.0, 0.0).with_scale(Vec3::splat(2.0));
+        let aabb = Shape::circle(1.5).aabb(&transform);
+
+        assert_eq!(aabb.min, Vec2::new(-4.0, -3.0));
+        assert_eq!(aabb.max, Vec2::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn aabb_rotated_capsule() {
+        let transform = Transform::from_xyz(0.0, 1.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+        let aabb = Shape::capsule(Vec2::new(-2.0, 0.0), Vec2::new(2.0, 0.0), 0.5).aabb(&transform);
+
+        assert_abs_diff_eq!(
+            Vector2::from(aabb.min),
+            Vector2::from(Vec2::new(-0.5, -1.5)),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(aabb.max),
+            Vector2::from(Vec2::new(0.5, 3.5)),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn heightfield_cells() {
+        let heightfield = Heightfield::new(vec![0.0; 11], 2.0);
+        assert_eq!(heightfield.width(), 20.0);
+        assert_eq!(heightfield.vertex(0), Vec2::new(-10.0, 0.0));
+
+        assert_eq!(heightfield.cells_between(-1.0, 1.0), 4..6);
+        assert_eq!(heightfield.cells_between(0.0, 0.5), 5..6);
+        assert_eq!(heightfield.cells_between(-15.0, -9.0), 0..1);
+        assert_eq!(heightfield.cells_between(9.5, 15.0), 9..10);
+        assert!(heightfield.cells_between(11.0, 15.0).is_empty());
+        assert!(heightfield.cells_between(-15.0, -11.0).is_empty());
+    }
+
+    #[test]
+    fn aabb_heightfield() {
+        let transform = Transform::from_xyz(1.0, 0.0, 0.0);
+        let aabb = Shape::heightfield(vec![1.0, -2.0, 3.0], 1.5).aabb(&transform);
+
+        assert_eq!(aabb, Aabb::new(Vec2::new(-0.5, -2.0), Vec2::new(2.5, 3.0)));
+    }
+
+    #[test]
+    fn aabb_compound() {
+        let shape = Shape::compound(vec![
+            (
+                Transform2d::from_translation(Vec2::new(-2.0, 0.0)),
+                Shape::circle(1.0),
+            ),
+            (
+                Transform2d::new(Vec2::new(2.0, 0.0), std::f32::consts::FRAC_PI_2),
+                Shape::rect(4.0, 2.0),
+            ),
+        ]);
+        let aabb = shape.aabb(&Transform::from_xyz(0.0, 1.0, 0.0));
+
+        assert_abs_diff_eq!(
+            Vector2::from(aabb.min),
+            Vector2::from(Vec2::new(-3.0, -1.0)),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(aabb.max),
+            Vector2::from(Vec2::new(3.0, 3.0)),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn concave_polygon() {
+        let l_shape = Shape::concave_polygon(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, 0.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ])
+        .unwrap();
+        assert_eq!(l_shape.as_compound().unwrap().len(), 2);
+
+        let square = Shape::concave_polygon(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ])
+        .unwrap();
+        assert!(square.as_convex_polygon().is_some());
+    }
+
+    #[test]
+    fn invalid_concave_polygons() {
+        let bow_tie = vec![Vec2::ZERO, Vec2::ONE, Vec2::X, Vec2::Y];
+        assert_eq!(
+            Shape::concave_polygon(bow_tie).err(),
+            Some(ConvexPolygonError::SelfIntersecting)
+        );
+
+        let line = vec![Vec2::ZERO, Vec2::X, Vec2::new(2.0, 0.0)];
+        assert_eq!(
+            Shape::concave_polygon(line).err(),
+            Some(ConvexPolygonError::Degenerate)
+        );
+
+        assert_eq!(
+            Shape::concave_polygon(vec![Vec2::ZERO, Vec2::X]).err(),
+            Some(ConvexPolygonError::TooFewVertices(2))
+        );
+    }
+
+    #[test]
+    fn clockwise_polygon_is_reordered() {
+        let vertices = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 0.0),
+        ];
+        let polygon = ConvexPolygon::try_new(vertices).unwrap();
+
+        assert_eq!(
+            polygon.vertices(),
+            &[
+                Vec2::new(1.0, 0.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(0.0, 1.0),
+                Vec2::new(0.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn normals_and_axes() {
+        let polygon = ConvexPolygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        ]);
+
+        let normals: Vec<Vector2> = polygon
+            .normals()
+            .iter()
+            .copied()
+            .map(Vector2::from)
+            .collect();
+        let expected: Vec<Vector2> = [-Vec2::Y, -Vec2::Y, Vec2::ONE.normalize(), -Vec2::X]
+            .into_iter()
+            .map(Vector2::from)
+            .collect();
+        assert_abs_diff_eq!(normals.as_slice(), expected.as_slice());
+        // Both halves of the bottom side have the same axis
+        assert_eq!(polygon.axes(), &[(0, 2), (2, 3), (3, 0)]);
+    }
+
+    #[test]
+    fn rect_has_an_axis_per_side() {
+        let rect = Shape::rect(2.0, 1.0);
+        // Opposite sides are separate reference faces
+        assert_eq!(
+            rect.as_convex_polygon().unwrap().axes(),
+            &[(0, 1), (1, 2), (2, 3), (3, 0)]
+        );
+    }
+
+    #[test]
+    fn hull_of_degenerate_points() {
+        let square = Shape::convex_hull(&[
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.5, 0.5),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ])
+        .unwrap();
+        assert_eq!(square.as_convex_polygon().unwrap().vertices().len(), 4);
+
+        assert_eq!(
+            Shape::convex_hull(&[Vec2::ZERO, Vec2::ONE, Vec2::splat(2.0)]).err(),
+            Some(ConvexPolygonError::Degenerate)
+        );
+        assert_eq!(
+            Shape::convex_hull(&[Vec2::ONE; 4]).err(),
+            Some(ConvexPolygonError::Degenerate)
+        );
+        assert_eq!(
+            Shape::convex_hull(&[Vec2::ZERO, Vec2::X]).err(),
+            Some(ConvexPolygonError::TooFewVertices(2))
+        );
+    }
+
+    #[test]
+    fn invalid_polygons() {
+        assert_eq!(
+            ConvexPolygon::try_new(vec![Vec2::ZERO, Vec2::X]).err(),
+            Some(ConvexPolygonError::TooFewVertices(2))
+        );
+        assert_eq!(
+            ConvexPolygon::try_new(vec![Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::X]).err(),
+            Some(ConvexPolygonError::DuplicateVertex(1, 3))
+        );
+        assert_eq!(
+            ConvexPolygon::try_new(vec![Vec2::ZERO, Vec2::ONE, Vec2::splat(2.0)]).err(),
+            Some(ConvexPolygonError::Degenerate)
+        );
+
+        // Clockwise arrow pointing to the right with its notch at vertex 3
+        let arrow = vec![
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(0.0, 0.0),
+        ];
+        assert_eq!(
+            ConvexPolygon::try_new(arrow.clone()).err(),
+            Some(ConvexPolygonError::NotConvex(3))
+        );
+        // Counterclockwise the notch is the first vertex
+        assert_eq!(
+            ConvexPolygon::try_new(arrow.into_iter().rev().collect()).err(),
+            Some(ConvexPolygonError::NotConvex(0))
+        );
+
+        // Pentagram, which only turns left
+        let pentagram = (0..5)
+            .map(|i| {
+                let phi = (i * 2) as f32 * 2.0 * std::f32::consts::PI / 5.0;
+                Vec2::new(phi.cos(), phi.sin())
+            })
+            .collect();
+        assert_eq!(
+            ConvexPolygon::try_new(pentagram).err(),
+            Some(ConvexPolygonError::SelfIntersecting)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid convex polygon: a polygon needs at least 3 vertices")]
+    fn new_panics_with_description() {
+        ConvexPolygon::new(vec![Vec2::ZERO]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid capsule")]
+    fn capsule_without_length() {
+        Shape::capsule(Vec2::ONE, Vec2::ONE, 1.0);
+    }
+
+    #[test]
+    fn invalid_polygon_constructors() {
+        assert_eq!(
+            Shape::try_regular_polygon(1.0, 2).err(),
+            Some(ConvexPolygonError::TooFewVertices(2))
+        );
+        assert!(Shape::try_rect(0.0, 1.0).is_err());
+        assert!(Shape::try_rect(2.0, 1.0).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid convex polygon")]
+    fn regular_polygon_with_two_sides() {
+        Shape::regular_polygon(1.0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid segment")]
+    fn segment_without_length() {
+        Shape::segment(Vec2::ONE, Vec2::ONE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid polyline")]
+    fn polyline_with_single_vertex() {
+        Shape::polyline(vec![Vec2::ONE]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid polyline")]
+    fn polyline_with_repeated_vertex() {
+        Shape::one_sided_polyline(vec![Vec2::ZERO, Vec2::ONE, Vec2::ONE, Vec2::X]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid heightfield")]
+    fn heightfield_with_single_height() {
+        Shape::heightfield(vec![1.0], 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid heightfield")]
+    fn heightfield_without_spacing() {
+        Shape::heightfield(vec![1.0, 2.0], 0.0);
+    }
 }
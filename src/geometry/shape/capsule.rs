@@ -0,0 +1,50 @@
+use bevy::prelude::{Transform, Vec2};
+
+use crate::geometry::aabb::Aabb;
+
+/// Line segment with a radius around it
+pub struct Capsule {
+    a: Vec2,
+    b: Vec2,
+    radius: f32,
+}
+
+impl Capsule {
+    /// Capsule around the segment from `a` to `b`. Use a circle for a capsule without length.
+    ///
+    /// # Panics
+    ///
+    /// If both points are the same
+    pub fn new(a: Vec2, b: Vec2, radius: f32) -> Self {
+        assert!(a != b, "Invalid capsule: both end points are at {a}");
+        Self { a, b, radius }
+    }
+
+    /// Start point of the segment
+    pub fn a(&self) -> Vec2 {
+        self.a
+    }
+
+    /// End point of the segment
+    pub fn b(&self) -> Vec2 {
+        self.b
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Radius in world coordinates. The ends stay round, so it is scaled by the largest scale
+    /// factor of the transform.
+    pub fn world_radius(&self, transform: &Transform) -> f32 {
+        self.radius * transform.scale.truncate().abs().max_element()
+    }
+
+    /// Bounding box of the capsule in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        Aabb::from_points(
+            [self.a, self.b].map(|v| transform.transform_point(v.extend(0.0)).truncate()),
+        )
+        .expanded(self.world_radius(transform))
+    }
+}
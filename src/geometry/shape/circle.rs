@@ -1,3 +1,7 @@
+use bevy::prelude::{Transform, Vec2};
+
+use crate::geometry::aabb::Aabb;
+
 pub struct Circle {
     radius: f32,
 }
@@ -10,4 +14,18 @@ impl Circle {
     pub fn radius(&self) -> f32 {
         self.radius
     }
+
+    /// Radius in world coordinates. The circle stays round, so it is scaled by the largest scale
+    /// factor of the transform.
+    pub fn world_radius(&self, transform: &Transform) -> f32 {
+        self.radius * transform.scale.truncate().abs().max_element()
+    }
+
+    /// Bounding box of the circle in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        Aabb::from_center(
+            transform.translation.truncate(),
+            Vec2::splat(self.world_radius(transform)),
+        )
+    }
 }
@@ -1,26 +1,199 @@
-use bevy::prelude::Vec2;
+use std::{error::Error, fmt};
+
+use bevy::prelude::{Transform, Vec2};
+use itertools::Itertools;
+
+use crate::{geometry::aabb::Aabb, math::convex_hull};
+
+/// Reason why vertices do not describe a convex polygon
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvexPolygonError {
+    /// Less than three vertices were given
+    TooFewVertices(usize),
+    /// The vertex at the second index is the same as the one at the first index
+    DuplicateVertex(usize, usize),
+    /// All vertices lie on a line, so the polygon has no area
+    Degenerate,
+    /// The polygon turns the other way at the vertex with this index
+    NotConvex(usize),
+    /// The edges wind around the polygon more than once
+    SelfIntersecting,
+}
+
+impl fmt::Display for ConvexPolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFewVertices(count) => {
+                write!(
+                    f,
+                    "a polygon needs at least 3 vertices, but {count} were given"
+                )
+            }
+            Self::DuplicateVertex(first, second) => {
+                write!(f, "vertex {second} is a duplicate of vertex {first}")
+            }
+            Self::Degenerate => write!(f, "all vertices lie on a line"),
+            Self::NotConvex(index) => write!(f, "the polygon is concave at vertex {index}"),
+            Self::SelfIntersecting => write!(f, "the edges of the polygon intersect each other"),
+        }
+    }
+}
+
+impl Error for ConvexPolygonError {}
 
 pub struct ConvexPolygon {
     vertices: Vec<Vec2>,
-    // normals: Vec<Vec2>,
+    /// Outward normal of the edge starting at the vertex with the same index
+    normals: Vec<Vec2>,
+    /// Straight sides as the indices of their first and last vertex
+    axes: Vec<(usize, usize)>,
 }
 
 impl ConvexPolygon {
+    /// Polygon through the vertices, see [`ConvexPolygon::try_new`]
+    ///
+    /// # Panics
+    ///
+    /// If the vertices do not describe a convex polygon
     pub fn new(vertices: Vec<Vec2>) -> Self {
-        // let normals: Vec<Vec2> = vertices
-        //     .iter()
-        //     .circular_tuple_windows()
-        //     .map(|(&p1, &p2)| (p2 - p1).perp())
-        //     .collect();
-        // Self { vertices, normals }
-        Self { vertices }
+        Self::try_new(vertices).unwrap_or_else(|err| panic!("Invalid convex polygon: {err}"))
+    }
+
+    /// Polygon through the vertices, which are reordered counterclockwise if given clockwise.
+    ///
+    /// Fails if there are less than three different vertices, the polygon has no area, is concave
+    /// or intersects itself. Vertices in the middle of straight edges are allowed.
+    pub fn try_new(mut vertices: Vec<Vec2>) -> Result<Self, ConvexPolygonError> {
+        if vertices.len() < 3 {
+            return Err(ConvexPolygonError::TooFewVertices(vertices.len()));
+        }
+        if let Some((i, j)) = (0..vertices.len())
+            .tuple_combinations()
+            .find(|&(i, j)| vertices[i] == vertices[j])
+        {
+            return Err(ConvexPolygonError::DuplicateVertex(i, j));
+        }
+
+        // Tolerance for straight corners depending on the size of the polygon
+        let size = vertices
+            .iter()
+            .circular_tuple_windows()
+            .map(|(p1, p2)| p1.distance_squared(*p2))
+            .fold(0.0, f32::max);
+        let epsilon = 1e-6 * size;
+
+        let signed_area: f32 = vertices
+            .iter()
+            .circular_tuple_windows()
+            .map(|(p1, p2)| p1.perp_dot(*p2))
+            .sum();
+        if signed_area.abs() <= epsilon {
+            return Err(ConvexPolygonError::Degenerate);
+        }
+        let clockwise = signed_area < 0.0;
+        if clockwise {
+            vertices.reverse();
+        }
+        // Index in the original order
+        let index = |i: usize| {
+            if clockwise {
+                vertices.len() - 1 - i
+            } else {
+                i
+            }
+        };
+
+        let mut winding = 0.0;
+        for (i, (a, b, c)) in vertices.iter().circular_tuple_windows().enumerate() {
+            let (edge, next_edge) = (*b - *a, *c - *b);
+            if edge.perp_dot(next_edge) < -epsilon {
+                return Err(ConvexPolygonError::NotConvex(index(
+                    (i + 1) % vertices.len(),
+                )));
+            }
+            winding += edge.angle_between(next_edge);
+        }
+        // Every convex corner turns left, so the polygon winds around exactly once
+        if winding > 3.0 * std::f32::consts::PI {
+            return Err(ConvexPolygonError::SelfIntersecting);
+        }
+
+        let normals: Vec<Vec2> = vertices
+            .iter()
+            .circular_tuple_windows()
+            .map(|(&v1, &v2)| -(v2 - v1).perp().normalize())
+            .collect();
+        // Edges in the middle of a straight side share the normal of the edge before them
+        let side_starts: Vec<usize> = (0..normals.len())
+            .filter(|&i| {
+                let previous = normals[(i + normals.len() - 1) % normals.len()];
+                previous.dot(normals[i]) < 1.0 - f32::EPSILON
+            })
+            .collect();
+        // Every side ends where the next one starts
+        let axes = side_starts
+            .iter()
+            .copied()
+            .circular_tuple_windows()
+            .collect();
+
+        Ok(Self {
+            vertices,
+            normals,
+            axes,
+        })
+    }
+
+    /// Smallest convex polygon enclosing all points, see [`ConvexPolygon::try_from_hull`]
+    ///
+    /// # Panics
+    ///
+    /// If there are less than three points or all points lie on a line
+    pub fn from_hull(points: &[Vec2]) -> Self {
+        Self::try_from_hull(points).unwrap_or_else(|err| panic!("Invalid convex polygon: {err}"))
+    }
+
+    /// Smallest convex polygon enclosing all points, e.g. the vertices of a sprite outline.
+    ///
+    /// The vertices are in counterclockwise order without points on the edges. Fails if there are
+    /// less than three points or all points lie on a line.
+    pub fn try_from_hull(points: &[Vec2]) -> Result<Self, ConvexPolygonError> {
+        let hull = convex_hull(points);
+        if hull.len() < 3 && points.len() >= 3 {
+            // Duplicate or collinear points were dropped
+            return Err(ConvexPolygonError::Degenerate);
+        }
+        Self::try_new(hull)
     }
 
+    /// Vertices in counterclockwise order
     pub fn vertices(&self) -> &Vec<Vec2> {
         &self.vertices
     }
 
-    // pub fn normals(&self) -> &Vec<Vec2> {
-    //     &self.normals
-    // }
+    /// Bounding box of the polygon in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        Aabb::from_points(
+            self.vertices
+                .iter()
+                .map(|&v| transform.transform_point(v.extend(0.0)).truncate()),
+        )
+    }
+
+    /// Outward normals of the edges, the edge `i` goes from vertex `i` to vertex `i + 1`
+    pub fn normals(&self) -> &Vec<Vec2> {
+        &self.normals
+    }
+
+    /// Straight sides of the polygon whose normals are the separating axes to test, as the
+    /// indices of their first and last vertex.
+    ///
+    /// Edges along the same straight side of the polygon are only listed once, the normal of the
+    /// side is the one of the edge starting at its first vertex. Opposite parallel sides are both
+    /// kept: the narrow phase measures the separation from each side to the other shape on one
+    /// side only and uses the best side as reference face for the contacts, and ray casts and
+    /// point queries need every side as a boundary of the polygon.
+    pub fn axes(&self) -> &Vec<(usize, usize)> {
+        &self.axes
+    }
 }
@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+use bevy::prelude::{Transform, Vec2};
+
+use crate::geometry::aabb::Aabb;
+
+/// Terrain surface through evenly spaced height samples, solid below.
+///
+/// The samples are centered horizontally around the origin. Shapes only collide with the top of
+/// the surface, so shapes which got completely below it are not pushed back up. Like
+/// [`Polyline`](super::polyline::Polyline) it has no mass and should only be used for fixed
+/// bodies.
+pub struct Heightfield {
+    heights: Vec<f32>,
+    scale: f32,
+}
+
+impl Heightfield {
+    /// Surface through the heights, which are `scale` apart horizontally.
+    ///
+    /// # Panics
+    ///
+    /// If there are less than two heights or the scale is not positive
+    pub fn new(heights: Vec<f32>, scale: f32) -> Self {
+        assert!(
+            heights.len() >= 2,
+            "Invalid heightfield: {} heights given, at least two are needed",
+            heights.len()
+        );
+        assert!(
+            scale > 0.0,
+            "Invalid heightfield: scale {scale} is not positive"
+        );
+        Self { heights, scale }
+    }
+
+    pub fn heights(&self) -> &Vec<f32> {
+        &self.heights
+    }
+
+    /// Horizontal distance between two samples
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Horizontal extent of the surface
+    pub fn width(&self) -> f32 {
+        self.heights.len().saturating_sub(1) as f32 * self.scale
+    }
+
+    /// Position of the sample in local coordinates
+    pub fn vertex(&self, i: usize) -> Vec2 {
+        Vec2::new(i as f32 * self.scale - self.width() / 2.0, self.heights[i])
+    }
+
+    /// Cells touching the horizontal range in local coordinates. Cell `i` lies between the samples
+    /// `i` and `i + 1`.
+    pub fn cells_between(&self, min_x: f32, max_x: f32) -> Range<usize> {
+        let last_sample = self.heights.len().saturating_sub(1) as f32;
+        let sample = |x: f32| (x + self.width() / 2.0) / self.scale;
+        let first = sample(min_x).floor().clamp(0.0, last_sample) as usize;
+        let last = sample(max_x).ceil().clamp(0.0, last_sample) as usize;
+        first..last.max(first)
+    }
+
+    /// Bounding box of the surface in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        let min_height = self.heights.iter().copied().fold(f32::MAX, f32::min);
+        let max_height = self.heights.iter().copied().fold(f32::MIN, f32::max);
+        let half_width = self.width() / 2.0;
+        Aabb::from_points(
+            [
+                Vec2::new(-half_width, min_height),
+                Vec2::new(half_width, min_height),
+                Vec2::new(half_width, max_height),
+                Vec2::new(-half_width, max_height),
+            ]
+            .map(|v| transform.transform_point(v.extend(0.0)).truncate()),
+        )
+    }
+}
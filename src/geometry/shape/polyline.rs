@@ -0,0 +1,73 @@
+use bevy::prelude::{Transform, Vec2};
+use itertools::Itertools;
+
+use crate::geometry::aabb::Aabb;
+
+/// Open chain of segments, e.g. the outline of level geometry.
+///
+/// Every inner vertex acts as ghost vertex for its two segments: shapes sliding across it do not
+/// catch on the seam between the segments. Like [`Segment`](super::segment::Segment) it has no
+/// mass and should only be used for fixed bodies.
+pub struct Polyline {
+    vertices: Vec<Vec2>,
+    one_sided: bool,
+}
+
+impl Polyline {
+    /// Chain through the vertices colliding on both sides.
+    ///
+    /// # Panics
+    ///
+    /// If there are less than two vertices or two consecutive vertices are the same
+    pub fn new(vertices: Vec<Vec2>) -> Self {
+        Self::chain(vertices, false)
+    }
+
+    /// Chain through the vertices only colliding with shapes on the right side of it, which is the
+    /// outside of a counterclockwise outline. Shapes on the other side pass through.
+    ///
+    /// # Panics
+    ///
+    /// If there are less than two vertices or two consecutive vertices are the same
+    pub fn one_sided(vertices: Vec<Vec2>) -> Self {
+        Self::chain(vertices, true)
+    }
+
+    fn chain(vertices: Vec<Vec2>, one_sided: bool) -> Self {
+        assert!(
+            vertices.len() >= 2,
+            "Invalid polyline: {} vertices given, at least two are needed",
+            vertices.len()
+        );
+        if let Some((a, _)) = vertices.iter().tuple_windows().find(|(a, b)| a == b) {
+            panic!("Invalid polyline: segment without length at {a}");
+        }
+        Self {
+            vertices,
+            one_sided,
+        }
+    }
+
+    pub fn vertices(&self) -> &Vec<Vec2> {
+        &self.vertices
+    }
+
+    /// Whether only the right side of the chain collides
+    pub fn is_one_sided(&self) -> bool {
+        self.one_sided
+    }
+
+    /// Segments of the chain as pairs of start and end point
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.vertices.iter().copied().tuple_windows()
+    }
+
+    /// Bounding box of the chain in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        Aabb::from_points(
+            self.vertices
+                .iter()
+                .map(|&v| transform.transform_point(v.extend(0.0)).truncate()),
+        )
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::{Transform, Vec2};
+
+use crate::geometry::aabb::Aabb;
+
+/// Straight line between two points, colliding on both sides.
+///
+/// It has no area and therefore no mass, so it should only be used for fixed bodies.
+pub struct Segment {
+    a: Vec2,
+    b: Vec2,
+}
+
+impl Segment {
+    /// Segment from `a` to `b`
+    ///
+    /// # Panics
+    ///
+    /// If both points are the same
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        assert!(a != b, "Invalid segment: both end points are at {a}");
+        Self { a, b }
+    }
+
+    /// Start point of the segment
+    pub fn a(&self) -> Vec2 {
+        self.a
+    }
+
+    /// End point of the segment
+    pub fn b(&self) -> Vec2 {
+        self.b
+    }
+
+    /// Bounding box of the segment in world coordinates
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        Aabb::from_points(
+            [self.a, self.b].map(|v| transform.transform_point(v.extend(0.0)).truncate()),
+        )
+    }
+}
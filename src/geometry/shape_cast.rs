@@ -0,0 +1,368 @@
+use bevy::prelude::*;
+
+use super::{
+    aabb::Aabb,
+    gjk::{closest_points, collide_convex, SupportMap},
+    shape::{segment::Segment, Shape},
+};
+
+/// Distance at which a moving shape counts as touching the other shape. The shape stops short of
+/// the other shape by about half of it, so it does not start overlapping when moved on.
+const CONTACT_DISTANCE: f32 = 1e-3;
+
+/// Maximal number of steps the moving shape is advanced towards the other shape
+const MAX_ITERATIONS: usize = 64;
+
+/// First contact of a moving shape with another shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeHit {
+    /// Time of impact, the shape has moved by `toi * velocity` when it touches the other shape,
+    /// up to a small gap
+    pub toi: f32,
+    /// Contact point on the other shape in world coordinates
+    pub point: Vec2,
+    /// Normal of the surface of the other shape, pointing against the movement
+    pub normal: Vec2,
+}
+
+/// First contact of `shape` moving from `transform` with the velocity against the resting `other`
+/// shape, up to the maximal time of impact.
+///
+/// The moving shape has to be convex or a compound of convex shapes. Shapes overlapping at the
+/// start hit immediately, chains are only hit on their colliding side.
+pub fn cast_shape(
+    shape: &Shape,
+    transform: &Transform,
+    velocity: Vec2,
+    other: &Shape,
+    other_transform: &Transform,
+    max_toi: f32,
+) -> Option<ShapeHit> {
+    match (shape, other) {
+        (Shape::Compound(shapes), _) => {
+            first_hit(shapes.iter().filter_map(|(child_transform, child)| {
+                let transform = transform.mul_transform((*child_transform).into());
+                cast_shape(child, &transform, velocity, other, other_transform, max_toi)
+            }))
+        }
+        (_, Shape::Compound(shapes)) => {
+            first_hit(shapes.iter().filter_map(|(child_transform, child)| {
+                let other_transform = other_transform.mul_transform((*child_transform).into());
+                cast_shape(shape, transform, velocity, child, &other_transform, max_toi)
+            }))
+        }
+        (_, Shape::Polyline(polyline)) => {
+            let shape = shape.as_support_map()?;
+            first_hit(polyline.segments().filter_map(|(a, b)| {
+                let segment = Segment::new(a, b);
+                let hit = cast_convex(
+                    shape,
+                    transform,
+                    velocity,
+                    &segment,
+                    other_transform,
+                    max_toi,
+                )?;
+                hits_front(&segment, other_transform, velocity, polyline.is_one_sided())
+                    .then_some(hit)
+            }))
+        }
+        (_, Shape::Heightfield(heightfield)) => {
+            let shape_support_map = shape.as_support_map()?;
+
+            // Cells below the path of the shape
+            let swept_aabb = swept_aabb(shape, transform, velocity, max_toi);
+            let inverse = other_transform.compute_affine().inverse();
+            let local_aabb = Aabb::from_points(
+                [
+                    swept_aabb.min,
+                    Vec2::new(swept_aabb.max.x, swept_aabb.min.y),
+                    swept_aabb.max,
+                    Vec2::new(swept_aabb.min.x, swept_aabb.max.y),
+                ]
+                .map(|v| inverse.transform_point3(v.extend(0.0)).truncate()),
+            );
+            let cells = heightfield.cells_between(local_aabb.min.x, local_aabb.max.x);
+
+            // From right to left, so the top of the surface is the colliding side
+            first_hit(cells.filter_map(|i| {
+                let segment = Segment::new(heightfield.vertex(i + 1), heightfield.vertex(i));
+                let hit = cast_convex(
+                    shape_support_map,
+                    transform,
+                    velocity,
+                    &segment,
+                    other_transform,
+                    max_toi,
+                )?;
+                hits_front(&segment, other_transform, velocity, true).then_some(hit)
+            }))
+        }
+        _ => cast_convex(
+            shape.as_support_map()?,
+            transform,
+            velocity,
+            other.as_support_map()?,
+            other_transform,
+            max_toi,
+        ),
+    }
+}
+
+/// First contact of two convex shapes, see [`cast_shape`].
+///
+/// Uses conservative advancement: The moving shape is advanced by the time it needs to cover the
+/// distance to the other shape along the direction between their closest points. As the shapes are
+/// convex, they cannot touch earlier.
+pub fn cast_convex(
+    shape: &(impl SupportMap + ?Sized),
+    transform: &Transform,
+    velocity: Vec2,
+    other: &(impl SupportMap + ?Sized),
+    other_transform: &Transform,
+    max_toi: f32,
+) -> Option<ShapeHit> {
+    let mut toi = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        let moved = Transform {
+            translation: transform.translation + (velocity * toi).extend(0.0),
+            ..*transform
+        };
+
+        let Some(closest) = closest_points(shape, &moved, other, other_transform) else {
+            // Touching or already overlapping at the start
+            return Some(
+                match collide_convex(shape, &moved, other, other_transform) {
+                    Some(response) => ShapeHit {
+                        toi,
+                        point: response.contacts[0].point,
+                        normal: -response.normal,
+                    },
+                    None => ShapeHit {
+                        toi,
+                        point: shape.support_point(velocity, &moved),
+                        normal: -velocity.normalize_or_zero(),
+                    },
+                },
+            );
+        };
+
+        let normal = (closest.point - closest.other_point) / closest.distance;
+        if closest.distance <= CONTACT_DISTANCE {
+            return Some(ShapeHit {
+                toi,
+                point: closest.other_point,
+                normal,
+            });
+        }
+
+        let approach = -velocity.dot(normal);
+        if approach <= 0.0 {
+            // Moving away from the other shape
+            return None;
+        }
+        // Stop short of the other shape, so the closest points are still defined
+        toi += (closest.distance - CONTACT_DISTANCE / 2.0) / approach;
+        if toi > max_toi {
+            return None;
+        }
+    }
+    None
+}
+
+/// Hit with the smallest time of impact
+fn first_hit(hits: impl IntoIterator<Item = ShapeHit>) -> Option<ShapeHit> {
+    hits.into_iter()
+        .min_by(|hit1, hit2| hit1.toi.total_cmp(&hit2.toi))
+}
+
+/// Whether a shape moving with the velocity can hit the segment of a chain. One-sided chains only
+/// collide on the right side, as seen along the segment.
+fn hits_front(segment: &Segment, transform: &Transform, velocity: Vec2, one_sided: bool) -> bool {
+    let [a, b] =
+        [segment.a(), segment.b()].map(|v| transform.transform_point(v.extend(0.0)).truncate());
+    !one_sided || velocity.dot(-(b - a).perp()) < 0.0
+}
+
+/// Bounding box of the shape along its whole path
+pub fn swept_aabb(shape: &Shape, transform: &Transform, velocity: Vec2, max_toi: f32) -> Aabb {
+    let aabb = shape.aabb(transform);
+    // Without movement along an axis an infinite time of impact does not matter
+    let motion = Vec2::select(velocity.cmpeq(Vec2::ZERO), Vec2::ZERO, velocity * max_toi);
+    aabb.merged(&Aabb::new(aabb.min + motion, aabb.max + motion))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{geometry::transform::Transform2d, math::Vector2};
+
+    use super::*;
+
+    #[test]
+    fn circle_falls_on_rect() {
+        let hit = cast_shape(
+            &Shape::circle(0.5),
+            &Transform::from_xyz(0.3, 5.0, 0.0),
+            Vec2::new(0.0, -2.0),
+            &Shape::rect(4.0, 2.0),
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .unwrap();
+
+        // Falls by 3.5 with 2 per unit of time
+        assert_abs_diff_eq!(hit.toi, 1.75, epsilon = 1e-3);
+        assert_abs_diff_eq!(
+            Vector2::from(hit.point),
+            Vector2::from(Vec2::new(0.3, 1.0)),
+            epsilon = 1e-3
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(hit.normal),
+            Vector2::from(Vec2::Y),
+            epsilon = 1e-3
+        );
+
+        assert!(cast_shape(
+            &Shape::circle(0.5),
+            &Transform::from_xyz(0.3, 5.0, 0.0),
+            Vec2::new(0.0, -2.0),
+            &Shape::rect(4.0, 2.0),
+            &Transform::IDENTITY,
+            1.5,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn rect_slides_into_rotated_rect() {
+        let hit = cast_shape(
+            &Shape::rect(1.0, 1.0),
+            &Transform::from_xyz(-5.0, 0.0, 0.0),
+            Vec2::X,
+            &Shape::rect(2.0, 2.0),
+            &Transform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            f32::MAX,
+        )
+        .unwrap();
+
+        // The right side of the box hits the left corner of the diamond
+        assert_abs_diff_eq!(hit.toi, 5.0 - 0.5 - 2.0f32.sqrt(), epsilon = 1e-3);
+        assert_abs_diff_eq!(
+            Vector2::from(hit.point),
+            Vector2::from(Vec2::new(-(2.0f32.sqrt()), 0.0)),
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn shapes_moving_apart_or_overlapping() {
+        let rect = Shape::rect(2.0, 2.0);
+        assert!(cast_shape(
+            &rect,
+            &Transform::from_xyz(3.0, 0.0, 0.0),
+            Vec2::X,
+            &rect,
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .is_none());
+
+        let hit = cast_shape(
+            &rect,
+            &Transform::from_xyz(1.5, 0.0, 0.0),
+            Vec2::X,
+            &rect,
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .unwrap();
+        assert_eq!(hit.toi, 0.0);
+        assert_abs_diff_eq!(
+            Vector2::from(hit.normal),
+            Vector2::from(Vec2::X),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn capsule_falls_through_back_of_one_sided_polyline() {
+        let polyline = Shape::one_sided_polyline(vec![Vec2::new(2.0, 0.0), Vec2::new(-2.0, 0.0)]);
+        let capsule = Shape::capsule(Vec2::new(0.0, -0.5), Vec2::new(0.0, 0.5), 0.25);
+
+        let hit = cast_shape(
+            &capsule,
+            &Transform::from_xyz(0.0, 3.0, 0.0),
+            -Vec2::Y,
+            &polyline,
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .unwrap();
+        assert_abs_diff_eq!(hit.toi, 2.25, epsilon = 1e-3);
+        assert_abs_diff_eq!(
+            Vector2::from(hit.normal),
+            Vector2::from(Vec2::Y),
+            epsilon = 1e-3
+        );
+
+        assert!(cast_shape(
+            &capsule,
+            &Transform::from_xyz(0.0, -3.0, 0.0),
+            Vec2::Y,
+            &polyline,
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn rect_slides_down_heightfield() {
+        let heightfield = Shape::heightfield(vec![2.0, 1.0, 0.0, 0.0, 0.0], 1.0);
+        let hit = cast_shape(
+            &Shape::rect(0.5, 0.5),
+            &Transform::from_xyz(1.5, 3.0, 0.0),
+            -Vec2::Y,
+            &heightfield,
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .unwrap();
+
+        assert_abs_diff_eq!(hit.toi, 2.75, epsilon = 1e-3);
+        assert_abs_diff_eq!(
+            Vector2::from(hit.normal),
+            Vector2::from(Vec2::Y),
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn compound_hits_with_lower_child() {
+        let dumbbell = Shape::compound(vec![
+            (
+                Transform2d::from_translation(Vec2::new(-1.0, 0.0)),
+                Shape::circle(0.5),
+            ),
+            (
+                Transform2d::from_translation(Vec2::new(1.0, -1.0)),
+                Shape::circle(0.5),
+            ),
+        ]);
+        let hit = cast_shape(
+            &dumbbell,
+            &Transform::from_xyz(0.0, 4.0, 0.0),
+            -Vec2::Y,
+            &Shape::rect(4.0, 2.0),
+            &Transform::IDENTITY,
+            f32::MAX,
+        )
+        .unwrap();
+
+        assert_abs_diff_eq!(hit.toi, 1.5, epsilon = 1e-3);
+        assert_abs_diff_eq!(hit.point.x, 1.0, epsilon = 1e-2);
+    }
+}
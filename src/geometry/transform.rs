@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+/// Position and rotation in the plane, e.g. of a child shape relative to its collider
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Transform2d {
+    pub translation: Vec2,
+    /// Rotation in [rad], counterclockwise positive
+    pub rotation: f32,
+}
+
+impl Transform2d {
+    pub fn new(translation: Vec2, rotation: f32) -> Self {
+        Self {
+            translation,
+            rotation,
+        }
+    }
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self::new(translation, 0.0)
+    }
+
+    /// Moves a point from the local coordinates of this transform to the parent coordinates
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.translation + Vec2::from_angle(self.rotation).rotate(point)
+    }
+}
+
+impl From<Transform2d> for Transform {
+    fn from(value: Transform2d) -> Self {
+        Transform::from_translation(value.translation.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(value.rotation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::Vector2;
+
+    use super::*;
+
+    #[test]
+    fn transform_point() {
+        let transform = Transform2d::new(Vec2::new(1.0, 2.0), std::f32::consts::FRAC_PI_2);
+        let point = Vec2::new(3.0, 0.0);
+
+        assert_abs_diff_eq!(
+            Vector2::from(transform.transform_point(point)),
+            Vector2::from(Vec2::new(1.0, 5.0)),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            Vector2::from(
+                Transform::from(transform)
+                    .transform_point(point.extend(0.0))
+                    .truncate()
+            ),
+            Vector2::from(Vec2::new(1.0, 5.0)),
+            epsilon = 1e-6
+        );
+    }
+}
@@ -1,9 +1,9 @@
-mod geometry;
+pub mod geometry;
 mod math;
-mod physics;
+pub mod physics;
 mod player;
-mod plugin;
-mod render;
+pub mod plugin;
+pub mod render;
 
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
@@ -14,7 +14,10 @@ use rand::{thread_rng, Rng};
 use render::ArcanePhysics2DDebugRenderPlugin;
 
 use crate::{
-    physics::rigid_body::{RigidBody, RigidBodyType, Velocity},
+    physics::{
+        forces::Gravity,
+        rigid_body::{RigidBody, RigidBodyType, Velocity},
+    },
     player::Player,
 };
 
@@ -30,6 +33,8 @@ pub fn entry_point() {
             ..Default::default()
         }))
         .insert_resource(ClearColor(Color::BLACK))
+        // Top down view, so nothing should fall
+        .insert_resource(Gravity(Vec2::ZERO))
         .add_plugin(ShapePlugin)
         .add_plugin(ArcanePhysicsPlugin2D::default())
         .add_plugin(WorldInspectorPlugin::new())
@@ -127,13 +132,24 @@ fn setup(
         .spawn((
             MaterialMesh2dBundle {
                 mesh: meshes
-                    .add(shape::Quad::new(Vec2::new(OBJECT_SIZE, OBJECT_SIZE)).into())
+                    .add(
+                        shape::Capsule {
+                            radius: OBJECT_SIZE / 4.0,
+                            depth: OBJECT_SIZE / 2.0,
+                            ..default()
+                        }
+                        .into(),
+                    )
                     .into(),
                 material: materials.add(ColorMaterial::from(Color::ORANGE)),
                 transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
                 ..default()
             },
-            Collider::rect(2.0, 2.0),
+            Collider::capsule(
+                Vec2::new(0.0, -OBJECT_SIZE / 4.0),
+                Vec2::new(0.0, OBJECT_SIZE / 4.0),
+                OBJECT_SIZE / 4.0,
+            ),
             RigidBody {
                 body_type: RigidBodyType::Dynamic,
             },
@@ -243,4 +259,40 @@ fn setup(
             },
         ))
         .insert(Name::new("Arrow"));
+
+    // L-shaped platform
+    //  ___
+    // |   |
+    // |   |______
+    // |__________|
+    let vertices = vec![
+        Vec2::new(-OBJECT_SIZE, OBJECT_SIZE),
+        Vec2::new(-OBJECT_SIZE, -OBJECT_SIZE / 2.0),
+        Vec2::new(OBJECT_SIZE, -OBJECT_SIZE / 2.0),
+        Vec2::new(OBJECT_SIZE, 0.0),
+        Vec2::new(-OBJECT_SIZE / 2.0, 0.0),
+        Vec2::new(-OBJECT_SIZE / 2.0, OBJECT_SIZE),
+    ];
+    let shape = shapes::Polygon {
+        points: vertices.clone(),
+        closed: true,
+    };
+    commands
+        .spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                transform: Transform::from_translation(Vec3::new(
+                    -3.0 * OBJECT_SIZE,
+                    -2.0 * OBJECT_SIZE,
+                    0.0,
+                )),
+                ..Default::default()
+            },
+            Fill::color(Color::GOLD),
+            Collider::concave_polygon(vertices).expect("The platform is a simple polygon"),
+            RigidBody {
+                body_type: RigidBodyType::Fixed,
+            },
+        ))
+        .insert(Name::new("Platform"));
 }
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use bevy::prelude::*;
+use itertools::Itertools;
 
 #[cfg(test)]
 use approx::AbsDiffEq;
@@ -37,3 +38,283 @@ impl From<Vec2> for Vector2 {
         Self(value)
     }
 }
+
+/// Area of a simple polygon (shoelace formula)
+pub fn polygon_area(points: &[Vec2]) -> f32 {
+    let sum: f32 = points
+        .iter()
+        .circular_tuple_windows()
+        .map(|(p1, p2)| (p1.y + p2.y) * (p1.x - p2.x))
+        .sum();
+    0.5 * sum.abs()
+}
+
+/// Centroid (center of area) of a simple polygon
+pub fn polygon_centroid(points: &[Vec2]) -> Vec2 {
+    let mut centroid = Vec2::ZERO;
+    let mut area = 0.0;
+
+    for (p1, p2) in points.iter().circular_tuple_windows() {
+        let cross = p1.perp_dot(*p2);
+        centroid += (*p1 + *p2) * cross;
+        area += cross;
+    }
+
+    if area != 0.0 {
+        centroid /= 3.0 * area;
+    }
+
+    centroid
+}
+
+/// Second moment of area of a simple polygon about the origin (polar moment)
+pub fn polygon_inertia(points: &[Vec2]) -> f32 {
+    let sum: f32 = points
+        .iter()
+        .circular_tuple_windows()
+        .map(|(p1, p2)| p1.perp_dot(*p2) * (p1.dot(*p1) + p1.dot(*p2) + p2.dot(*p2)))
+        .sum();
+    (sum / 12.0).abs()
+}
+
+/// Point on the segment from `a` to `b` closest to the given point
+pub fn closest_point_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length_sq = ab.length_squared();
+    if length_sq == 0.0 {
+        return a;
+    }
+    a + ab * ((point - a).dot(ab) / length_sq).clamp(0.0, 1.0)
+}
+
+/// Closest points between the segments from `a1` to `b1` and from `a2` to `b2`
+pub fn closest_points_between_segments(a1: Vec2, b1: Vec2, a2: Vec2, b2: Vec2) -> (Vec2, Vec2) {
+    let d1 = b1 - a1;
+    let d2 = b2 - a2;
+    let r = a1 - a2;
+    let (len1_sq, len2_sq) = (d1.length_squared(), d2.length_squared());
+
+    // Parameters of the closest points along the segments
+    let (s, t) = if len1_sq == 0.0 && len2_sq == 0.0 {
+        (0.0, 0.0)
+    } else if len1_sq == 0.0 {
+        (0.0, (d2.dot(r) / len2_sq).clamp(0.0, 1.0))
+    } else if len2_sq == 0.0 {
+        ((-d1.dot(r) / len1_sq).clamp(0.0, 1.0), 0.0)
+    } else {
+        let (c, f) = (d1.dot(r), d2.dot(r));
+        let b = d1.dot(d2);
+        let denom = len1_sq * len2_sq - b * b;
+        // Parallel segments have no unique pair, so start with any point of the first one
+        let s = if denom > 0.0 {
+            ((b * f - c * len2_sq) / denom).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let t = (b * s + f) / len2_sq;
+        if t < 0.0 {
+            ((-c / len1_sq).clamp(0.0, 1.0), 0.0)
+        } else if t > 1.0 {
+            (((b - c) / len1_sq).clamp(0.0, 1.0), 1.0)
+        } else {
+            (s, t)
+        }
+    };
+
+    (a1 + d1 * s, a2 + d2 * t)
+}
+
+/// Convex hull of the points in counterclockwise order (Andrew's monotone chain).
+///
+/// Points on the edges of the hull and duplicates are dropped, so every vertex is a real corner.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.sort_by(|p1, p2| p1.x.total_cmp(&p2.x).then(p1.y.total_cmp(&p2.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Appends the point to the chain after removing all points which do not turn left anymore
+    let push = |chain: &mut Vec<Vec2>, point: Vec2, min_len: usize| {
+        while chain.len() >= min_len {
+            let (a, b) = (chain[chain.len() - 2], chain[chain.len() - 1]);
+            if (b - a).perp_dot(point - b) > 0.0 {
+                break;
+            }
+            chain.pop();
+        }
+        chain.push(point);
+    };
+
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() + 1);
+    // Lower hull from left to right
+    for &point in &points {
+        push(&mut hull, point, 2);
+    }
+    // Upper hull from right to left
+    let lower_len = hull.len() + 1;
+    for &point in points.iter().rev().skip(1) {
+        push(&mut hull, point, lower_len);
+    }
+    // The last point is the first one again
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn area_rectangle() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        assert_eq!(polygon_area(&points), 10.0 * 10.0);
+    }
+
+    #[test]
+    fn area_hexagon() {
+        let cos_60 = 60.0_f32.to_radians().cos();
+        let sin_60 = 60.0_f32.to_radians().sin();
+        let points = [
+            Vec2::new(1.0, 0.0),
+            Vec2::new(cos_60, sin_60),
+            Vec2::new(-cos_60, sin_60),
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(-cos_60, -sin_60),
+            Vec2::new(cos_60, -sin_60),
+        ];
+        let edge_length: f32 = 1.0;
+        let area_regular_hexagon = (3.0 * 3.0_f32.sqrt() / 2.0) * (edge_length).powi(2);
+        assert_abs_diff_eq!(
+            polygon_area(&points),
+            area_regular_hexagon,
+            epsilon = 2.4e-7
+        );
+    }
+
+    #[test]
+    fn area_arrow() {
+        // (10,  -2) _____(15, -2) [1]
+        //    [0]   |     \
+        //          |      \ (18, -6) [2]
+        //          |      /
+        //    [4]   |_____/
+        // (10, -10)      (15, -10) [3]
+        let points = [
+            Vec2::new(10.0, -2.0),
+            Vec2::new(15.0, -2.0),
+            Vec2::new(18.0, -6.0),
+            Vec2::new(15.0, -10.0),
+            Vec2::new(10.0, -10.0),
+        ];
+        let area_rectangle = ((points[1].x - points[0].x) * (points[4].y - points[0].y)).abs();
+        let area_triangle = 0.5 * ((points[3].y - points[1].y) * (points[2].x - points[1].x)).abs();
+        assert_eq!(polygon_area(&points), area_rectangle + area_triangle);
+    }
+
+    #[test]
+    fn centroid_shifted_rectangle() {
+        let points = [
+            Vec2::new(10.0, 50.0),
+            Vec2::new(10.0, 150.0),
+            Vec2::new(30.0, 150.0),
+            Vec2::new(30.0, 50.0),
+        ];
+        let centroid = polygon_centroid(&points);
+        assert_abs_diff_eq!(centroid.x, 20.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(centroid.y, 100.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn inertia_rectangle() {
+        let (w, h) = (2.0_f32, 4.0_f32);
+        let points = [
+            Vec2::new(w / 2.0, h / 2.0),
+            Vec2::new(w / 2.0, -h / 2.0),
+            Vec2::new(-w / 2.0, -h / 2.0),
+            Vec2::new(-w / 2.0, h / 2.0),
+        ];
+        assert_abs_diff_eq!(
+            polygon_inertia(&points),
+            w * h * (w.powi(2) + h.powi(2)) / 12.0
+        );
+    }
+
+    #[test]
+    fn closest_points_of_crossing_segments() {
+        let (p1, p2) = closest_points_between_segments(
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.5, -1.0),
+            Vec2::new(0.5, 1.0),
+        );
+        assert_eq!(p1, Vec2::new(0.5, 0.0));
+        assert_eq!(p2, Vec2::new(0.5, 0.0));
+    }
+
+    #[test]
+    fn closest_points_of_skewed_segments() {
+        let (p1, p2) = closest_points_between_segments(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(4.0, 3.0),
+        );
+        assert_eq!(p1, Vec2::new(2.0, 0.0));
+        assert_eq!(p2, Vec2::new(3.0, 1.0));
+
+        let (p1, p2) = closest_points_between_segments(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 2.0),
+            Vec2::new(1.0, 1.0),
+        );
+        assert_eq!(p1, Vec2::new(1.0, 0.0));
+        assert_eq!(p2, Vec2::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn convex_hull_drops_inner_and_collinear_points() {
+        let points = [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.5, 1.5),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(0.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn convex_hull_of_collinear_points() {
+        let points = [
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            [Vec2::new(0.0, 0.0), Vec2::new(2.0, 2.0)]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+/// Acceleration due to gravity acting on all dynamic bodies in [m/s^2]
+#[derive(Resource, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Resource)]
+pub struct Gravity(pub Vec2);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self(Vec2::new(0.0, -9.81))
+    }
+}
+
+/// Factor the [`Gravity`] is multiplied with for a single body
+#[derive(Component, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct GravityScale(pub f32);
+
+impl Default for GravityScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Force acting on a rigid body every step until it is changed or removed
+#[derive(Component, Default, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct ExternalForce {
+    /// Force in [N]
+    pub force: Vec2,
+    /// Point of application in world coordinates. Applied at the center of mass if not set.
+    pub point: Option<Vec2>,
+}
+
+impl ExternalForce {
+    /// Force acting on the center of mass
+    pub fn new(force: Vec2) -> Self {
+        Self { force, point: None }
+    }
+
+    /// Force acting on a point in world coordinates, which also rotates the body
+    pub fn at_point(force: Vec2, point: Vec2) -> Self {
+        Self {
+            force,
+            point: Some(point),
+        }
+    }
+
+    /// Torque of the force around the given center of mass in world coordinates
+    pub fn torque(&self, center_of_mass: Vec2) -> f32 {
+        self.point
+            .map_or(0.0, |point| (point - center_of_mass).perp_dot(self.force))
+    }
+}
+
+/// Torque acting on a rigid body every step until it is changed or removed
+#[derive(Component, Default, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct ExternalTorque {
+    /// Torque in [N m], counterclockwise positive
+    pub torque: f32,
+}
+
+/// Impulse acting on a rigid body once during the next step. It is reset afterwards.
+#[derive(Component, Default, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct ExternalImpulse {
+    /// Linear impulse in [N s] applied at the center of mass
+    pub impulse: Vec2,
+    /// Angular impulse in [N m s], counterclockwise positive
+    pub torque_impulse: f32,
+}
+
+impl ExternalImpulse {
+    /// Adds an impulse acting on the center of mass
+    pub fn apply_impulse(&mut self, impulse: Vec2) {
+        self.impulse += impulse;
+    }
+
+    /// Adds an angular impulse
+    pub fn apply_torque_impulse(&mut self, torque_impulse: f32) {
+        self.torque_impulse += torque_impulse;
+    }
+
+    /// Adds an impulse acting on a point in world coordinates.
+    ///
+    /// The `center_of_mass` of the body has to be given in world coordinates as well, see
+    /// [`MassProperties::world_center_of_mass`](super::mass_properties::MassProperties::world_center_of_mass).
+    pub fn apply_impulse_at_point(&mut self, impulse: Vec2, point: Vec2, center_of_mass: Vec2) {
+        self.impulse += impulse;
+        self.torque_impulse += (point - center_of_mass).perp_dot(impulse);
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.impulse == Vec2::ZERO && self.torque_impulse == 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse_at_point_accumulates_torque() {
+        let mut impulse = ExternalImpulse::default();
+        impulse.apply_impulse_at_point(Vec2::X, Vec2::new(0.0, 1.0), Vec2::ZERO);
+        impulse.apply_impulse_at_point(Vec2::X, Vec2::new(0.0, -1.0), Vec2::ZERO);
+
+        assert_eq!(impulse.impulse, Vec2::new(2.0, 0.0));
+        assert_eq!(impulse.torque_impulse, 0.0);
+
+        impulse.apply_impulse_at_point(Vec2::Y, Vec2::new(3.0, 1.0), Vec2::new(1.0, 1.0));
+        assert_eq!(impulse.impulse, Vec2::new(2.0, 1.0));
+        assert_eq!(impulse.torque_impulse, 2.0);
+    }
+
+    #[test]
+    fn force_at_center_has_no_torque() {
+        assert_eq!(ExternalForce::new(Vec2::X).torque(Vec2::ONE), 0.0);
+        assert_eq!(
+            ExternalForce::at_point(Vec2::Y, Vec2::new(2.0, 0.0)).torque(Vec2::ZERO),
+            2.0
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+
+use crate::{
+    geometry::{collider::Collider, shape::Shape},
+    math::{polygon_area, polygon_centroid, polygon_inertia},
+};
+
+/// Density of a collider in [kg/m^2]
+#[derive(Component, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct Density(pub f32);
+
+impl Default for Density {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Mass and inertia of a rigid body. Derived from the [`Collider`] and its [`Density`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct MassProperties {
+    /// Mass in [kg]
+    pub mass: f32,
+    /// Inverse of the mass, zero for massless bodies
+    pub inv_mass: f32,
+    /// Center of mass in local coordinates of the body
+    pub center_of_mass: Vec2,
+    /// Moment of inertia around the center of mass in [kg m^2]
+    pub inertia: f32,
+    /// Inverse of the moment of inertia, zero for massless bodies
+    pub inv_inertia: f32,
+}
+
+impl Default for MassProperties {
+    /// Unit mass and inertia, used until the real properties are computed
+    fn default() -> Self {
+        Self::new(1.0, Vec2::ZERO, 1.0)
+    }
+}
+
+impl MassProperties {
+    pub fn new(mass: f32, center_of_mass: Vec2, inertia: f32) -> Self {
+        Self {
+            mass,
+            inv_mass: if mass > 0.0 { 1.0 / mass } else { 0.0 },
+            center_of_mass,
+            inertia,
+            inv_inertia: if inertia > 0.0 { 1.0 / inertia } else { 0.0 },
+        }
+    }
+
+    /// Center of mass in world coordinates for a body with the given transform
+    pub fn world_center_of_mass(&self, transform: &Transform) -> Vec2 {
+        transform
+            .transform_point(self.center_of_mass.extend(0.0))
+            .truncate()
+    }
+
+    /// Calculates the mass properties of a shape with uniform density
+    pub fn from_shape(shape: &Shape, density: f32) -> Self {
+        match shape {
+            Shape::Circle(circle) => {
+                let radius_sq = circle.radius().powi(2);
+                let mass = density * std::f32::consts::PI * radius_sq;
+                Self::new(mass, Vec2::ZERO, 0.5 * mass * radius_sq)
+            }
+            Shape::ConvexPolygon(polygon) => {
+                let vertices = polygon.vertices();
+                let mass = density * polygon_area(vertices);
+                let center_of_mass = polygon_centroid(vertices);
+                // Parallel axis theorem to move the inertia from the origin to the center of mass
+                let inertia =
+                    density * polygon_inertia(vertices) - mass * center_of_mass.length_squared();
+                Self::new(mass, center_of_mass, inertia)
+            }
+            Shape::Capsule(capsule) => {
+                let length = capsule.a().distance(capsule.b());
+                let radius_sq = capsule.radius().powi(2);
+                let rect_mass = density * 2.0 * capsule.radius() * length;
+                let circle_mass = density * std::f32::consts::PI * radius_sq;
+
+                // Both half circles are shifted by half the length plus the distance of their
+                // centroid from the flat side
+                let half_length = 0.5 * length;
+                let centroid_offset = 4.0 * capsule.radius() / (3.0 * std::f32::consts::PI);
+                let circle_inertia = circle_mass
+                    * (0.5 * radius_sq + half_length.powi(2) + 2.0 * half_length * centroid_offset);
+                let rect_inertia = rect_mass * (4.0 * radius_sq + length.powi(2)) / 12.0;
+
+                Self::new(
+                    rect_mass + circle_mass,
+                    (capsule.a() + capsule.b()) / 2.0,
+                    circle_inertia + rect_inertia,
+                )
+            }
+            // Without an area there is no mass
+            Shape::Segment(segment) => Self::new(0.0, (segment.a() + segment.b()) / 2.0, 0.0),
+            Shape::Polyline(_) | Shape::Heightfield(_) => Self::new(0.0, Vec2::ZERO, 0.0),
+            Shape::Compound(shapes) => {
+                let parts: Vec<Self> = shapes
+                    .iter()
+                    .map(|(transform, shape)| {
+                        let props = Self::from_shape(shape, density);
+                        Self {
+                            center_of_mass: transform.transform_point(props.center_of_mass),
+                            ..props
+                        }
+                    })
+                    .collect();
+
+                let mass: f32 = parts.iter().map(|part| part.mass).sum();
+                let center_of_mass = if mass > 0.0 {
+                    parts
+                        .iter()
+                        .map(|part| part.mass * part.center_of_mass)
+                        .sum::<Vec2>()
+                        / mass
+                } else {
+                    Vec2::ZERO
+                };
+                // Parallel axis theorem to move the inertia of every part to the common center
+                let inertia = parts
+                    .iter()
+                    .map(|part| {
+                        part.inertia
+                            + part.mass * part.center_of_mass.distance_squared(center_of_mass)
+                    })
+                    .sum();
+                Self::new(mass, center_of_mass, inertia)
+            }
+        }
+    }
+}
+
+/// Filter for colliders whose mass properties have to be (re)computed
+type MassPropertiesOutdated = Or<(Changed<Collider>, Changed<Density>)>;
+
+/// Computes the mass properties of new or changed colliders and colliders with changed density
+pub fn update_mass_properties(
+    mut commands: Commands,
+    query: Query<(Entity, &Collider, Option<&Density>), MassPropertiesOutdated>,
+) {
+    for (entity, collider, density) in &query {
+        let density = density.copied().unwrap_or_default().0;
+        commands
+            .entity(entity)
+            .insert(MassProperties::from_shape(&collider.shape, density));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::geometry::transform::Transform2d;
+
+    use super::*;
+
+    #[test]
+    fn circle() {
+        let props = MassProperties::from_shape(&Shape::circle(2.0), 3.0);
+
+        let mass = 3.0 * std::f32::consts::PI * 4.0;
+        assert_abs_diff_eq!(props.mass, mass);
+        assert_abs_diff_eq!(props.inv_mass, 1.0 / mass);
+        assert_eq!(props.center_of_mass, Vec2::ZERO);
+        assert_abs_diff_eq!(props.inertia, 0.5 * mass * 4.0);
+    }
+
+    #[test]
+    fn rect() {
+        let (w, h) = (2.0, 4.0);
+        let props = MassProperties::from_shape(&Shape::rect(w, h), 0.5);
+
+        let mass = 0.5 * w * h;
+        assert_abs_diff_eq!(props.mass, mass);
+        assert_abs_diff_eq!(props.center_of_mass.x, 0.0);
+        assert_abs_diff_eq!(props.center_of_mass.y, 0.0);
+        assert_abs_diff_eq!(props.inertia, mass * (w * w + h * h) / 12.0);
+        assert_abs_diff_eq!(props.inv_inertia, 12.0 / (mass * (w * w + h * h)));
+    }
+
+    #[test]
+    fn offset_polygon_inertia_around_center_of_mass() {
+        let (w, h) = (2.0, 4.0);
+        let vertices = vec![
+            Vec2::new(10.0, 10.0),
+            Vec2::new(10.0 + w, 10.0),
+            Vec2::new(10.0 + w, 10.0 + h),
+            Vec2::new(10.0, 10.0 + h),
+        ];
+        let props = MassProperties::from_shape(&Shape::convex_polygon(vertices), 1.0);
+
+        let mass = w * h;
+        assert_abs_diff_eq!(props.center_of_mass.x, 11.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(props.center_of_mass.y, 12.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(props.inertia, mass * (w * w + h * h) / 12.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn replaced_collider_updates_mass_properties() {
+        let mut world = World::new();
+        let entity = world.spawn(Collider::circle(1.0)).id();
+        let mut schedule = Schedule::new();
+        schedule.add_systems((update_mass_properties, apply_system_buffers).chain());
+
+        schedule.run(&mut world);
+        let circle = *world.get::<MassProperties>(entity).unwrap();
+        assert_abs_diff_eq!(circle.mass, std::f32::consts::PI);
+
+        *world.get_mut::<Collider>(entity).unwrap() = Collider::rect(2.0, 3.0);
+        schedule.run(&mut world);
+        let rect = *world.get::<MassProperties>(entity).unwrap();
+        assert_abs_diff_eq!(rect.mass, 6.0);
+    }
+
+    #[test]
+    fn capsule() {
+        let (length, radius) = (4.0, 1.0);
+        let props = MassProperties::from_shape(
+            &Shape::capsule(Vec2::new(0.0, -2.0), Vec2::new(0.0, 2.0), radius),
+            1.0,
+        );
+
+        let mass = 2.0 * radius * length + std::f32::consts::PI * radius * radius;
+        assert_abs_diff_eq!(props.mass, mass);
+        assert_eq!(props.center_of_mass, Vec2::ZERO);
+        // Bounded by the inertia of the enclosing rect and the inner rect
+        let outer_mass = 2.0 * radius * (length + 2.0 * radius);
+        let outer = outer_mass * (4.0 * radius * radius + (length + 2.0 * radius).powi(2)) / 12.0;
+        let inner_mass = 2.0 * radius * length;
+        let inner = inner_mass * (4.0 * radius * radius + length * length) / 12.0;
+        assert!(inner < props.inertia && props.inertia < outer);
+    }
+
+    #[test]
+    fn compound_of_two_rects() {
+        // Two halves of a 4 x 2 rect, one of them rotated into place
+        let props = MassProperties::from_shape(
+            &Shape::compound(vec![
+                (
+                    Transform2d::from_translation(Vec2::new(-1.0, 3.0)),
+                    Shape::rect(2.0, 2.0),
+                ),
+                (
+                    Transform2d::new(Vec2::new(1.0, 3.0), std::f32::consts::FRAC_PI_2),
+                    Shape::rect(2.0, 2.0),
+                ),
+            ]),
+            2.0,
+        );
+        let rect = MassProperties::from_shape(&Shape::rect(4.0, 2.0), 2.0);
+
+        assert_abs_diff_eq!(props.mass, rect.mass);
+        assert_abs_diff_eq!(props.center_of_mass.x, 0.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(props.center_of_mass.y, 3.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(props.inertia, rect.inertia, epsilon = 1e-4);
+    }
+}
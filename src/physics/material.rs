@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+/// Surface properties of a body used when resolving contacts
+#[derive(Component, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct PhysicsMaterial {
+    /// The coefficient of restitution (COR, also denoted by e), is the ratio of the final to
+    /// initial relative speed between two objects after they collide.
+    ///
+    /// It normally ranges from 0 to 1 where 1 would be a perfectly elastic collision.
+    /// A perfectly inelastic collision has a coefficient of 0, but a 0 value does not have to be
+    /// perfectly inelastic
+    pub restitution: f32,
+    /// Coefficient of friction which has to be overcome to start sliding
+    pub static_friction: f32,
+    /// Coefficient of friction while sliding
+    pub dynamic_friction: f32,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            restitution: 0.5,
+            static_friction: 0.6,
+            dynamic_friction: 0.4,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    /// Combines the materials of two bodies in contact.
+    ///
+    /// The restitution takes the less bouncy of both, the friction coefficients the geometric mean.
+    pub fn combine(&self, other: &Self) -> Self {
+        Self {
+            restitution: self.restitution.min(other.restitution),
+            static_friction: (self.static_friction * other.static_friction).sqrt(),
+            dynamic_friction: (self.dynamic_friction * other.dynamic_friction).sqrt(),
+        }
+    }
+}
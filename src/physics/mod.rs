@@ -1 +1,6 @@
+pub mod forces;
+pub mod mass_properties;
+pub mod material;
 pub mod rigid_body;
+pub mod solver;
+pub mod time;
@@ -1,4 +1,10 @@
-use bevy::prelude::*;
+use bevy::{ecs::query::WorldQuery, prelude::*};
+
+use super::{
+    forces::{ExternalForce, ExternalImpulse, ExternalTorque, Gravity, GravityScale},
+    mass_properties::MassProperties,
+    time::SubstepTime,
+};
 
 pub enum RigidBodyType {
     /// Affected by all external forces.
@@ -14,18 +20,103 @@ pub struct RigidBody {
     pub body_type: RigidBodyType,
 }
 
+impl RigidBody {
+    /// Inverse mass of the body, or zero if the body is not moved by collisions
+    pub fn inverse_mass(&self, mass_properties: &MassProperties) -> f32 {
+        match self.body_type {
+            RigidBodyType::Dynamic => mass_properties.inv_mass,
+            _ => 0.0,
+        }
+    }
+
+    /// Inverse moment of inertia of the body, or zero if the body is not rotated by collisions
+    pub fn inverse_inertia(&self, mass_properties: &MassProperties) -> f32 {
+        match self.body_type {
+            RigidBodyType::Dynamic => mass_properties.inv_inertia,
+            _ => 0.0,
+        }
+    }
+}
+
 #[derive(Component, Default, Reflect, FromReflect)]
 #[reflect(Component)]
 pub struct Velocity {
-    /// Linear velocity
+    /// Linear velocity in [m/s]
     pub lin_vel: Vec2,
-    /// Angular velocity
-    pub ang_vel: Vec2,
+    /// Angular velocity in [rad/s], counterclockwise positive
+    pub ang_vel: f32,
+}
+
+impl Velocity {
+    /// Velocity of a point fixed to the body, given its offset from the center of mass
+    pub fn point_velocity(&self, offset: Vec2) -> Vec2 {
+        self.lin_vel + self.ang_vel * offset.perp()
+    }
 }
 
-pub fn apply_velocity(mut query: Query<(&mut Transform, &Velocity)>, time_step: Res<FixedTime>) {
-    for (mut transform, velocity) in &mut query {
-        transform.translation.x += velocity.lin_vel.x * time_step.period.as_secs_f32();
-        transform.translation.y += velocity.lin_vel.y * time_step.period.as_secs_f32();
+/// Components of a rigid body needed to integrate the forces acting on it
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct ForceQuery {
+    pub body: &'static RigidBody,
+    pub transform: &'static Transform,
+    pub velocity: &'static mut Velocity,
+    pub mass_properties: Option<&'static MassProperties>,
+    pub force: Option<&'static ExternalForce>,
+    pub torque: Option<&'static ExternalTorque>,
+    pub impulse: Option<&'static mut ExternalImpulse>,
+    pub gravity_scale: Option<&'static GravityScale>,
+}
+
+/// Changes the velocity of dynamic bodies according to gravity and the external forces and impulses
+pub fn integrate_forces(mut query: Query<ForceQuery>, gravity: Res<Gravity>, time: SubstepTime) {
+    let dt = time.delta_seconds();
+    for mut item in &mut query {
+        if let RigidBodyType::Dynamic = item.body.body_type {
+            let scale = item.gravity_scale.copied().unwrap_or_default().0;
+            item.velocity.lin_vel += gravity.0 * scale * dt;
+        }
+
+        let mass_properties = item.mass_properties.copied().unwrap_or_default();
+        let inv_mass = item.body.inverse_mass(&mass_properties);
+        let inv_inertia = item.body.inverse_inertia(&mass_properties);
+        let center_of_mass = mass_properties.world_center_of_mass(item.transform);
+
+        if let Some(force) = item.force {
+            item.velocity.lin_vel += force.force * inv_mass * dt;
+            item.velocity.ang_vel += force.torque(center_of_mass) * inv_inertia * dt;
+        }
+        if let Some(torque) = item.torque {
+            item.velocity.ang_vel += torque.torque * inv_inertia * dt;
+        }
+        if let Some(impulse) = &mut item.impulse {
+            if !impulse.is_zero() {
+                item.velocity.lin_vel += impulse.impulse * inv_mass;
+                item.velocity.ang_vel += impulse.torque_impulse * inv_inertia;
+                **impulse = ExternalImpulse::default();
+            }
+        }
+    }
+}
+
+pub fn apply_velocity(
+    mut query: Query<(&mut Transform, &Velocity, Option<&MassProperties>)>,
+    time: SubstepTime,
+) {
+    let dt = time.delta_seconds();
+    for (mut transform, velocity, mass_properties) in &mut query {
+        transform.translation.x += velocity.lin_vel.x * dt;
+        transform.translation.y += velocity.lin_vel.y * dt;
+
+        if velocity.ang_vel != 0.0 {
+            // Rotate around the center of mass, which therefore has to stay in place
+            let center_of_mass = mass_properties
+                .map_or(Vec2::ZERO, |props| props.center_of_mass)
+                .extend(0.0);
+            let pivot = transform.transform_point(center_of_mass);
+            transform.rotate_z(velocity.ang_vel * dt);
+            let rotated_pivot = transform.transform_point(center_of_mass);
+            transform.translation += pivot - rotated_pivot;
+        }
     }
 }
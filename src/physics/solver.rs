@@ -0,0 +1,211 @@
+use bevy::prelude::Vec2;
+
+use super::material::PhysicsMaterial;
+
+/// Velocity state of a body taking part in a contact
+pub struct SolverBody {
+    /// Linear velocity
+    pub lin_vel: Vec2,
+    /// Angular velocity
+    pub ang_vel: f32,
+    /// Inverse of the mass. Zero for bodies which are not moved by collisions.
+    pub inv_mass: f32,
+    /// Inverse of the moment of inertia. Zero for bodies which are not rotated by collisions.
+    pub inv_inertia: f32,
+    /// Center of mass in world coordinates
+    pub center_of_mass: Vec2,
+}
+
+impl SolverBody {
+    /// Velocity of the body at the given offset from its center of mass
+    fn point_velocity(&self, offset: Vec2) -> Vec2 {
+        self.lin_vel + self.ang_vel * offset.perp()
+    }
+
+    fn apply_impulse(&mut self, impulse: Vec2, offset: Vec2) {
+        self.lin_vel += impulse * self.inv_mass;
+        self.ang_vel += offset.perp_dot(impulse) * self.inv_inertia;
+    }
+
+    /// Resistance of the body against an impulse in the given direction at the given offset
+    fn inv_effective_mass(&self, offset: Vec2, direction: Vec2) -> f32 {
+        self.inv_mass + offset.perp_dot(direction).powi(2) * self.inv_inertia
+    }
+}
+
+/// Applies the collision and friction impulses to two bodies touching at the contact `point`.
+///
+/// The `normal` has to point from the first to the second body.
+pub fn resolve_velocities(
+    body1: &mut SolverBody,
+    body2: &mut SolverBody,
+    point: Vec2,
+    normal: Vec2,
+    material: &PhysicsMaterial,
+) {
+    if body1.inv_mass + body2.inv_mass == 0.0 {
+        return;
+    }
+    let offset1 = point - body1.center_of_mass;
+    let offset2 = point - body2.center_of_mass;
+
+    let rel_vel = body2.point_velocity(offset2) - body1.point_velocity(offset1);
+    let vel_along_normal = rel_vel.dot(normal);
+    if vel_along_normal > 0.0 {
+        // Bodies are already separating
+        return;
+    }
+
+    let j = -(1.0 + material.restitution) * vel_along_normal
+        / (body1.inv_effective_mass(offset1, normal) + body2.inv_effective_mass(offset2, normal));
+    body1.apply_impulse(-j * normal, offset1);
+    body2.apply_impulse(j * normal, offset2);
+
+    // Friction acts against the sliding direction (Coulomb's law)
+    let rel_vel = body2.point_velocity(offset2) - body1.point_velocity(offset1);
+    let tangent = rel_vel - rel_vel.dot(normal) * normal;
+    if tangent.length_squared() <= f32::EPSILON {
+        return;
+    }
+    let tangent = tangent.normalize();
+
+    let jt = -rel_vel.dot(tangent)
+        / (body1.inv_effective_mass(offset1, tangent) + body2.inv_effective_mass(offset2, tangent));
+    let friction_impulse = if jt.abs() <= j * material.static_friction {
+        jt * tangent
+    } else {
+        -j * material.dynamic_friction * tangent
+    };
+    body1.apply_impulse(-friction_impulse, offset1);
+    body2.apply_impulse(friction_impulse, offset2);
+}
+
+/// Splits the penetration `depth` between both bodies according to their inverse masses.
+///
+/// Returns the distance each body has to be moved along the contact normal.
+pub fn separation(depth: f32, inv_mass1: f32, inv_mass2: f32) -> (f32, f32) {
+    let inv_mass_sum = inv_mass1 + inv_mass2;
+    if inv_mass_sum == 0.0 {
+        return (0.0, 0.0);
+    }
+    (
+        depth * inv_mass1 / inv_mass_sum,
+        depth * inv_mass2 / inv_mass_sum,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    fn body(lin_vel: Vec2, inv_mass: f32) -> SolverBody {
+        SolverBody {
+            lin_vel,
+            ang_vel: 0.0,
+            inv_mass,
+            inv_inertia: inv_mass,
+            center_of_mass: Vec2::ZERO,
+        }
+    }
+
+    fn frictionless(restitution: f32) -> PhysicsMaterial {
+        PhysicsMaterial {
+            restitution,
+            static_friction: 0.0,
+            dynamic_friction: 0.0,
+        }
+    }
+
+    #[test]
+    fn elastic_head_on_swaps_velocities() {
+        let mut body1 = body(Vec2::new(1.0, 0.0), 1.0);
+        let mut body2 = body(Vec2::new(-1.0, 0.0), 1.0);
+
+        resolve_velocities(
+            &mut body1,
+            &mut body2,
+            Vec2::ZERO,
+            Vec2::X,
+            &frictionless(1.0),
+        );
+
+        assert_abs_diff_eq!(body1.lin_vel.x, -1.0);
+        assert_abs_diff_eq!(body2.lin_vel.x, 1.0);
+    }
+
+    #[test]
+    fn bounce_off_fixed_body() {
+        let mut ball = body(Vec2::new(0.0, -2.0), 0.5);
+        let mut ground = body(Vec2::ZERO, 0.0);
+
+        resolve_velocities(
+            &mut ball,
+            &mut ground,
+            Vec2::ZERO,
+            -Vec2::Y,
+            &frictionless(0.5),
+        );
+
+        assert_abs_diff_eq!(ball.lin_vel.y, 1.0);
+        assert_eq!(ground.lin_vel, Vec2::ZERO);
+    }
+
+    #[test]
+    fn separating_bodies_are_untouched() {
+        let mut body1 = body(Vec2::new(-1.0, 0.0), 1.0);
+        let mut body2 = body(Vec2::new(1.0, 0.0), 1.0);
+
+        resolve_velocities(
+            &mut body1,
+            &mut body2,
+            Vec2::ZERO,
+            Vec2::X,
+            &frictionless(1.0),
+        );
+
+        assert_eq!(body1.lin_vel, Vec2::new(-1.0, 0.0));
+        assert_eq!(body2.lin_vel, Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn static_friction_stops_sliding() {
+        let mut slider = body(Vec2::new(0.1, -2.0), 1.0);
+        let mut ground = body(Vec2::ZERO, 0.0);
+        let material = PhysicsMaterial {
+            restitution: 0.0,
+            static_friction: 0.5,
+            dynamic_friction: 0.3,
+        };
+
+        resolve_velocities(&mut slider, &mut ground, Vec2::ZERO, -Vec2::Y, &material);
+
+        assert_abs_diff_eq!(slider.lin_vel.x, 0.0);
+        assert_abs_diff_eq!(slider.lin_vel.y, 0.0);
+    }
+
+    #[test]
+    fn off_center_impact_produces_spin() {
+        let mut ball = body(Vec2::new(1.0, 0.0), 1.0);
+        ball.center_of_mass = Vec2::new(-1.0, 1.0);
+        let mut block = body(Vec2::ZERO, 1.0);
+
+        // Ball hits the left side of the block above its center of mass
+        let contact = Vec2::new(-1.0, 1.0);
+        resolve_velocities(&mut ball, &mut block, contact, Vec2::X, &frictionless(1.0));
+
+        assert_eq!(ball.ang_vel, 0.0);
+        assert!(block.ang_vel < 0.0, "Block has to spin clockwise");
+        assert!(block.lin_vel.x > 0.0);
+        // The angular impulse equals the linear impulse times the lever arm
+        assert_abs_diff_eq!(block.ang_vel, -block.lin_vel.x);
+    }
+
+    #[test]
+    fn separation_by_inverse_mass() {
+        assert_eq!(separation(1.0, 1.0, 1.0), (0.5, 0.5));
+        assert_eq!(separation(1.0, 1.0, 0.0), (1.0, 0.0));
+        assert_eq!(separation(1.0, 0.0, 0.0), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::{
+    ecs::{schedule::ScheduleLabel, system::SystemParam},
+    prelude::*,
+};
+
+/// Schedule running a single substep of the physics simulation.
+///
+/// It is run [`SubstepCount`] times during every [`CoreSchedule::FixedUpdate`].
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsSubstep;
+
+/// Number of substeps every fixed physics step is split into, zero is treated as one
+#[derive(Resource, Clone, Copy, Reflect, FromReflect)]
+#[reflect(Resource)]
+pub struct SubstepCount(pub u32);
+
+impl Default for SubstepCount {
+    fn default() -> Self {
+        Self(4)
+    }
+}
+
+/// Time which passes during a single physics substep
+#[derive(SystemParam)]
+pub struct SubstepTime<'w> {
+    fixed_time: Res<'w, FixedTime>,
+    substeps: Res<'w, SubstepCount>,
+}
+
+impl SubstepTime<'_> {
+    /// Length of a substep in [s]
+    pub fn delta_seconds(&self) -> f32 {
+        self.fixed_time.period.as_secs_f32() / self.substeps.0.max(1) as f32
+    }
+}
+
+/// Runs the [`PhysicsSubstep`] schedule as often as configured by the [`SubstepCount`]
+pub fn run_substeps(world: &mut World) {
+    let substeps = world.resource::<SubstepCount>().0.max(1);
+    for _ in 0..substeps {
+        world.run_schedule(PhysicsSubstep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Runs(u32);
+
+    fn count_run(mut runs: ResMut<Runs>) {
+        runs.0 += 1;
+    }
+
+    #[test]
+    fn zero_substeps_still_step_once() {
+        let mut world = World::new();
+        world.insert_resource(SubstepCount(0));
+        world.init_resource::<Runs>();
+        world.init_resource::<Schedules>();
+        let mut substep = Schedule::new();
+        substep.add_system(count_run);
+        world.add_schedule(substep, PhysicsSubstep);
+
+        run_substeps(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 1);
+    }
+}
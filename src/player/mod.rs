@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::physics::rigid_body::Velocity;
+
 #[derive(Component)]
 pub struct Player;
 
@@ -8,14 +10,14 @@ const PLAYER_ROTATION_SPEED: f32 = 80.0; // [deg/s]
 
 pub fn move_player(
     keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<&mut Transform, With<Player>>,
+    mut query: Query<(&mut Transform, &mut Velocity), With<Player>>,
     mut last_time: Local<f32>,
     time: Res<Time>,
 ) {
     let dt = time.raw_elapsed_seconds() - *last_time;
     *last_time = time.raw_elapsed_seconds();
 
-    for mut player_transform in &mut query {
+    for (mut player_transform, mut velocity) in &mut query {
         let dir_x = match (
             keyboard_input.pressed(KeyCode::L) || keyboard_input.pressed(KeyCode::D),
             keyboard_input.pressed(KeyCode::H) || keyboard_input.pressed(KeyCode::A),
@@ -42,8 +44,7 @@ pub fn move_player(
             (false, true) => -1.0,
             _ => 0.0,
         };
-        player_transform.rotation *=
-            Quat::from_rotation_z(dt * PLAYER_ROTATION_SPEED.to_radians() * rot_dir)
-        // *objects.first_mut().unwrap().rotation_velocity_mut() = PLAYER_ROTATION_SPEED * rot_dir;
+        // Rotation is integrated by the physics steps
+        velocity.ang_vel = PLAYER_ROTATION_SPEED.to_radians() * rot_dir;
     }
 }
@@ -0,0 +1,126 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::geometry::collision::CollisionResponse;
+
+/// Sent when two colliders start touching
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionStarted(pub Entity, pub Entity);
+
+/// Sent when two colliders stop touching
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionEnded(pub Entity, pub Entity);
+
+/// Sent for every physics step during which two colliders touch
+#[derive(Clone, Debug)]
+pub struct Colliding {
+    pub entities: (Entity, Entity),
+    /// Contact manifold with the normal pointing from the first to the second entity
+    pub response: CollisionResponse,
+}
+
+/// Pairs of colliders touching during the last physics step
+#[derive(Resource, Default)]
+pub struct Collisions {
+    /// Colliding pairs with their latest contact manifold. The first entity is always the smaller.
+    current: HashMap<(Entity, Entity), CollisionResponse>,
+    /// Colliding pairs of the step before
+    previous: HashSet<(Entity, Entity)>,
+}
+
+impl Collisions {
+    /// Contact manifold of the two entities if they collided, seen from the first entity
+    pub fn get(&self, entity1: Entity, entity2: Entity) -> Option<CollisionResponse> {
+        if entity1 <= entity2 {
+            self.current.get(&(entity1, entity2)).cloned()
+        } else {
+            self.current
+                .get(&(entity2, entity1))
+                .cloned()
+                .map(CollisionResponse::flipped)
+        }
+    }
+
+    /// All colliding pairs with their contact manifold
+    pub fn iter(&self) -> impl Iterator<Item = (&(Entity, Entity), &CollisionResponse)> {
+        self.current.iter()
+    }
+
+    /// Records the collision of two entities during the current step
+    pub fn insert(&mut self, entity1: Entity, entity2: Entity, response: CollisionResponse) {
+        if entity1 <= entity2 {
+            self.current.insert((entity1, entity2), response);
+        } else {
+            self.current.insert((entity2, entity1), response.flipped());
+        }
+    }
+
+    /// Remembers the collisions of the last step and starts recording a new one
+    pub fn next_step(&mut self) {
+        self.previous.clear();
+        self.previous
+            .extend(self.current.drain().map(|(pair, _)| pair));
+    }
+}
+
+/// Compares the collisions of this step with the previous one and sends the events
+pub fn send_collision_events(
+    collisions: Res<Collisions>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ended: EventWriter<CollisionEnded>,
+    mut colliding: EventWriter<Colliding>,
+) {
+    for (&(entity1, entity2), response) in &collisions.current {
+        if !collisions.previous.contains(&(entity1, entity2)) {
+            started.send(CollisionStarted(entity1, entity2));
+        }
+        colliding.send(Colliding {
+            entities: (entity1, entity2),
+            response: response.clone(),
+        });
+    }
+    for &(entity1, entity2) in &collisions.previous {
+        if !collisions.current.contains_key(&(entity1, entity2)) {
+            ended.send(CollisionEnded(entity1, entity2));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Vec2;
+
+    use super::*;
+
+    fn response(normal: Vec2) -> CollisionResponse {
+        CollisionResponse {
+            normal,
+            depth: 0.1,
+            contacts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn pair_order_does_not_matter() {
+        let (entity1, entity2) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut collisions = Collisions::default();
+        collisions.insert(entity2, entity1, response(Vec2::X));
+
+        assert_eq!(collisions.get(entity2, entity1).unwrap().normal, Vec2::X);
+        assert_eq!(collisions.get(entity1, entity2).unwrap().normal, -Vec2::X);
+        assert_eq!(collisions.iter().count(), 1);
+    }
+
+    #[test]
+    fn next_step_forgets_current_collisions() {
+        let (entity1, entity2) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut collisions = Collisions::default();
+        collisions.insert(entity1, entity2, response(Vec2::Y));
+        collisions.next_step();
+
+        assert!(collisions.get(entity1, entity2).is_none());
+        assert!(collisions.previous.contains(&(entity1, entity2)));
+    }
+}
@@ -1,112 +1,231 @@
-use bevy::prelude::*;
-use bevy_prototype_debug_lines::DebugLines;
+use bevy::{ecs::query::WorldQuery, prelude::*};
 
 use crate::{
-    geometry::{collider::Collider, collision::CollisionWith, shape::Shape},
-    physics::rigid_body::{apply_velocity, RigidBody, RigidBodyType, Velocity},
+    geometry::{
+        broad_phase::{update_broad_phase, BroadPhase},
+        collider::{Collider, Sensor},
+        collision::CollisionWith,
+        filter::{filter_pairs, CollisionGroups, Group},
+    },
+    physics::{
+        forces::{ExternalForce, ExternalImpulse, ExternalTorque, Gravity, GravityScale},
+        mass_properties::{update_mass_properties, Density, MassProperties},
+        material::PhysicsMaterial,
+        rigid_body::{apply_velocity, integrate_forces, RigidBody, Velocity},
+        solver::{resolve_velocities, separation, SolverBody},
+        time::{run_substeps, PhysicsSubstep, SubstepCount},
+    },
     player::move_player,
 };
 
-#[derive(Default)]
-pub struct ArcanePhysicsPlugin2D {}
+use self::events::{
+    send_collision_events, Colliding, CollisionEnded, CollisionStarted, Collisions,
+};
+
+pub mod events;
+
+pub struct ArcanePhysicsPlugin2D {
+    /// Length of a fixed physics step in [s]
+    pub timestep: f32,
+    /// Number of substeps every physics step is split into
+    pub substeps: u32,
+}
+
+impl Default for ArcanePhysicsPlugin2D {
+    fn default() -> Self {
+        Self {
+            timestep: 1.0 / 60.0,
+            substeps: SubstepCount::default().0,
+        }
+    }
+}
 
-impl ArcanePhysicsPlugin2D {}
+impl ArcanePhysicsPlugin2D {
+    pub fn with_timestep(mut self, timestep: f32) -> Self {
+        self.timestep = timestep;
+        self
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `substeps` is zero.
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        assert!(substeps > 0, "At least one substep is needed");
+        self.substeps = substeps;
+        self
+    }
+}
 
 impl Plugin for ArcanePhysicsPlugin2D {
     fn build(&self, app: &mut App) {
         app.register_type::<Velocity>()
-            .add_event::<CollisionEvent>()
-            // add our system to the fixed timestep schedule
+            .register_type::<Sensor>()
+            .register_type::<Group>()
+            .register_type::<CollisionGroups>()
+            .register_type::<Density>()
+            .register_type::<MassProperties>()
+            .register_type::<PhysicsMaterial>()
+            .register_type::<ExternalForce>()
+            .register_type::<ExternalTorque>()
+            .register_type::<ExternalImpulse>()
+            .register_type::<Gravity>()
+            .register_type::<GravityScale>()
+            .register_type::<SubstepCount>()
+            .init_resource::<Gravity>()
+            .init_resource::<BroadPhase>()
+            .init_resource::<Collisions>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_event::<Colliding>()
+            // configure our fixed timestep schedule and how often it is split up
+            .insert_resource(FixedTime::new_from_secs(self.timestep))
+            .insert_resource(SubstepCount(self.substeps))
+            .init_schedule(PhysicsSubstep)
+            .add_systems(
+                (
+                    update_mass_properties,
+                    apply_system_buffers,
+                    collision_reset,
+                    run_substeps,
+                    // Spatial queries see the bodies where the last substep left them
+                    update_broad_phase,
+                    send_collision_events,
+                )
+                    .chain()
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
             .add_systems(
                 (
-                    collision_reset.before(check_for_collisions),
-                    apply_velocity.before(check_for_collisions),
-                    move_player
-                        .before(check_for_collisions)
-                        .after(apply_velocity),
+                    integrate_forces,
+                    apply_velocity,
+                    update_broad_phase,
+                    filter_pairs,
                     check_for_collisions,
-                ), // .in_schedule(CoreSchedule::FixedUpdate),
+                )
+                    .chain()
+                    .in_schedule(PhysicsSubstep),
             )
-            // configure our fixed timestep schedule to run twice a second
-            .insert_resource(FixedTime::new_from_secs(1.0 / 60.0));
+            .add_system(move_player);
     }
 }
 
-#[derive(Default)]
-pub struct CollisionEvent;
-
-pub fn collision_reset(mut query: Query<&mut Collider>) {
+pub fn collision_reset(mut query: Query<&mut Collider>, mut collisions: ResMut<Collisions>) {
     for mut collider in &mut query {
-        collider.collided = false;
+        // The flag does not change the shape, so the mass properties stay up to date
+        collider.bypass_change_detection().collided = false;
+    }
+    collisions.next_step();
+}
+
+/// Components of an entity taking part in the collision response
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct CollisionBodyQuery {
+    pub transform: &'static mut Transform,
+    pub collider: &'static mut Collider,
+    pub body: Option<&'static RigidBody>,
+    pub velocity: Option<&'static mut Velocity>,
+    pub mass_properties: Option<&'static MassProperties>,
+    pub material: Option<&'static PhysicsMaterial>,
+    pub sensor: Option<&'static Sensor>,
+}
+
+impl CollisionBodyQueryItem<'_> {
+    fn solver_body(&self) -> SolverBody {
+        let mass_properties = self.mass_properties.copied().unwrap_or_default();
+        let (inv_mass, inv_inertia) = match self.body {
+            Some(body) => (
+                body.inverse_mass(&mass_properties),
+                body.inverse_inertia(&mass_properties),
+            ),
+            // Entities without a rigid body are treated as fixed
+            None => (0.0, 0.0),
+        };
+
+        SolverBody {
+            lin_vel: self.velocity.as_ref().map_or(Vec2::ZERO, |v| v.lin_vel),
+            ang_vel: self.velocity.as_ref().map_or(0.0, |v| v.ang_vel),
+            inv_mass,
+            inv_inertia,
+            center_of_mass: mass_properties.world_center_of_mass(&self.transform),
+        }
+    }
+
+    fn apply_solver_body(&mut self, solver_body: &SolverBody) {
+        if solver_body.inv_mass == 0.0 {
+            return;
+        }
+        if let Some(velocity) = &mut self.velocity {
+            velocity.lin_vel = solver_body.lin_vel;
+            velocity.ang_vel = solver_body.ang_vel;
+        }
     }
 }
 
 pub fn check_for_collisions(
-    mut query: Query<(&mut Transform, &mut Collider, &Name, Option<&RigidBody>)>,
-    mut lines: ResMut<DebugLines>,
+    broad_phase: Res<BroadPhase>,
+    mut query: Query<CollisionBodyQuery>,
+    mut collisions: ResMut<Collisions>,
 ) {
-    let mut iter = query.iter_combinations_mut();
-    while let Some(
-        [(mut trafo1, mut collider1, name1, body1), (mut trafo2, mut collider2, name2, body2)],
-    ) = iter.fetch_next()
-    {
-        if let Some(collision) = match &collider1.shape {
-            Shape::Circle(circle) => match &collider2.shape {
-                Shape::Circle(other) => circle.collides(other, &trafo1, &trafo2, &mut lines),
-                Shape::ConvexPolygon(other) => circle.collides(other, &trafo1, &trafo2, &mut lines),
-            },
-            Shape::ConvexPolygon(polygon) => match &collider2.shape {
-                Shape::Circle(other) => polygon.collides(other, &trafo1, &trafo2, &mut lines),
-                Shape::ConvexPolygon(other) => {
-                    polygon.collides(other, &trafo1, &trafo2, &mut lines)
-                }
-            },
-        } {
-            // println!(
-            //     "Collision for body {name1} to {name2} with depth {} in direction {:?} occured",
-            //     collision.depth, collision.normal
-            // );
-            collider1.collided = true;
-            collider2.collided = true;
-            let type1 = match body1 {
-                Some(body) => &body.body_type,
-                None => &RigidBodyType::Fixed,
-            };
-            let type2 = match body2 {
-                Some(body) => &body.body_type,
-                None => &RigidBodyType::Fixed,
-            };
-            match (type1, type2) {
-                (RigidBodyType::Dynamic, RigidBodyType::Dynamic) => {
-                    trafo1.translation -= (collision.normal * collision.depth / 2.0).extend(0.0);
-                    trafo2.translation += (collision.normal * collision.depth / 2.0).extend(0.0);
-                    // println!(
-                    //     "  Moving body '{name1}' in direction {} with depth {}",
-                    //     -collision.normal,
-                    //     collision.depth / 2.0
-                    // );
-                    // println!(
-                    //     "  Moving body '{name2}' in direction {} with depth {}",
-                    //     collision.normal,
-                    //     collision.depth / 2.0
-                    // );
-                }
-                (RigidBodyType::Dynamic, RigidBodyType::Fixed) => {
-                    trafo1.translation -= (collision.normal * collision.depth).extend(0.0);
-                    // println!(
-                    //     "  Moving body '{name1}' in direction {} with depth {}",
-                    //     -collision.normal, collision.depth
-                    // );
-                }
-                (RigidBodyType::Fixed, RigidBodyType::Dynamic) => {
-                    trafo2.translation += (collision.normal * collision.depth).extend(0.0);
-                    // println!(
-                    //     "  Moving body '{name2}' in direction {} with depth {}",
-                    //     collision.normal, collision.depth
-                    // );
+    for &(entity1, entity2) in broad_phase.pairs() {
+        let Ok([mut body1, mut body2]) = query.get_many_mut([entity1, entity2]) else {
+            continue;
+        };
+        let (trafo1, trafo2) = (&*body1.transform, &*body2.transform);
+        if let Some(collision) =
+            body1
+                .collider
+                .shape
+                .collides(&body2.collider.shape, trafo1, trafo2)
+        {
+            body1.collider.bypass_change_detection().collided = true;
+            body2.collider.bypass_change_detection().collided = true;
+
+            if body1.sensor.is_some() || body2.sensor.is_some() {
+                // Sensors only detect the overlap
+                collisions.insert(entity1, entity2, collision);
+                continue;
+            }
+
+            let mut solver_body1 = body1.solver_body();
+            let mut solver_body2 = body2.solver_body();
+
+            // Position correction, so the bodies do not overlap anymore. Contacts pushing in the
+            // same direction share the correction.
+            let mut correction = Vec2::ZERO;
+            for contact in &collision.contacts {
+                let depth = contact.depth - correction.dot(contact.normal);
+                if depth <= 0.0 {
+                    continue;
                 }
-                (_, _) => (),
+                let (depth1, depth2) =
+                    separation(depth, solver_body1.inv_mass, solver_body2.inv_mass);
+                body1.transform.translation -= (contact.normal * depth1).extend(0.0);
+                body2.transform.translation += (contact.normal * depth2).extend(0.0);
+                solver_body1.center_of_mass -= contact.normal * depth1;
+                solver_body2.center_of_mass += contact.normal * depth2;
+                correction += contact.normal * depth;
+            }
+
+            // Velocity correction, so the bodies bounce off and slide along each other
+            let material = body1
+                .material
+                .copied()
+                .unwrap_or_default()
+                .combine(&body2.material.copied().unwrap_or_default());
+            for contact in &collision.contacts {
+                resolve_velocities(
+                    &mut solver_body1,
+                    &mut solver_body2,
+                    contact.point,
+                    contact.normal,
+                    &material,
+                );
             }
+            body1.apply_solver_body(&solver_body1);
+            body2.apply_solver_body(&solver_body2);
+
+            collisions.insert(entity1, entity2, collision);
         }
     }
 }
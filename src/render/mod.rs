@@ -19,6 +19,9 @@ impl Default for DebugRenderMode {
     }
 }
 
+/// Number of lines used to draw a half circle of a capsule
+const CAPSULE_ARC_SEGMENTS: usize = 8;
+
 pub struct ArcanePhysics2DDebugRenderPlugin {
     /// Enables the debug rendering
     pub enabled: bool,
@@ -61,26 +64,90 @@ fn debug_render(
             .rotation(transform.rotation)
             .color(color);
 
-        match &collider.shape {
-            Shape::Circle(circle) => {
-                shapes
-                    .circle()
-                    .position(transform.translation)
-                    .radius(circle.radius() * transform.scale.x)
-                    .rotation(transform.rotation)
-                    .color(color);
-            }
-            Shape::ConvexPolygon(polygon) => {
-                polygon
-                    .vertices()
-                    .iter()
-                    .circular_tuple_windows()
-                    .for_each(|(&p1, &p2)| {
-                        let start = transform.transform_point(p1.extend(0.0));
-                        let end = transform.transform_point(p2.extend(0.0));
-                        lines.line_colored(start, end, 0.0, color);
-                    });
+        draw_shape(&collider.shape, transform, color, &mut shapes, &mut lines);
+    }
+}
+
+/// Draws the outline of the shape with the given world transform
+fn draw_shape(
+    shape: &Shape,
+    transform: &Transform,
+    color: Color,
+    shapes: &mut DebugShapes,
+    lines: &mut DebugLines,
+) {
+    match shape {
+        Shape::Circle(circle) => {
+            shapes
+                .circle()
+                .position(transform.translation)
+                .radius(circle.radius() * transform.scale.x)
+                .rotation(transform.rotation)
+                .color(color);
+        }
+        Shape::ConvexPolygon(polygon) => {
+            polygon
+                .vertices()
+                .iter()
+                .circular_tuple_windows()
+                .for_each(|(&p1, &p2)| {
+                    let start = transform.transform_point(p1.extend(0.0));
+                    let end = transform.transform_point(p2.extend(0.0));
+                    lines.line_colored(start, end, 0.0, color);
+                });
+        }
+        Shape::Segment(segment) => {
+            let start = transform.transform_point(segment.a().extend(0.0));
+            let end = transform.transform_point(segment.b().extend(0.0));
+            lines.line_colored(start, end, 0.0, color);
+        }
+        Shape::Polyline(polyline) => {
+            polyline.segments().for_each(|(p1, p2)| {
+                let start = transform.transform_point(p1.extend(0.0));
+                let end = transform.transform_point(p2.extend(0.0));
+                lines.line_colored(start, end, 0.0, color);
+            });
+        }
+        Shape::Heightfield(heightfield) => {
+            (0..heightfield.heights().len())
+                .map(|i| heightfield.vertex(i))
+                .tuple_windows()
+                .for_each(|(p1, p2)| {
+                    let start = transform.transform_point(p1.extend(0.0));
+                    let end = transform.transform_point(p2.extend(0.0));
+                    lines.line_colored(start, end, 0.0, color);
+                });
+        }
+        Shape::Compound(children) => {
+            for (child_transform, child) in children {
+                let child_transform = transform.mul_transform((*child_transform).into());
+                draw_shape(child, &child_transform, color, shapes, lines);
             }
         }
+        Shape::Capsule(capsule) => {
+            let (a, b) = (capsule.a(), capsule.b());
+            let normal = -(b - a).perp().normalize() * capsule.radius();
+            let start_angle = normal.y.atan2(normal.x);
+
+            // Half circle around each end point, connected by the straight sides
+            let arc = |center: Vec2, start_angle: f32| {
+                (0..=CAPSULE_ARC_SEGMENTS).map(move |i| {
+                    let phi =
+                        start_angle + std::f32::consts::PI * i as f32 / CAPSULE_ARC_SEGMENTS as f32;
+                    center + capsule.radius() * Vec2::new(phi.cos(), phi.sin())
+                })
+            };
+            let outline: Vec<Vec2> = arc(b, start_angle)
+                .chain(arc(a, start_angle + std::f32::consts::PI))
+                .collect();
+            outline
+                .iter()
+                .circular_tuple_windows()
+                .for_each(|(&p1, &p2)| {
+                    let start = transform.transform_point(p1.extend(0.0));
+                    let end = transform.transform_point(p2.extend(0.0));
+                    lines.line_colored(start, end, 0.0, color);
+                });
+        }
     }
 }